@@ -0,0 +1,99 @@
+use crate::input_args::InputArgs;
+use clap::{ArgAction::Append, Parser};
+use emblem_core::AstDumper as EmblemAstDumper;
+
+/// Arguments to the ast subcommand
+#[derive(Clone, Debug, Parser, PartialEq, Eq)]
+#[warn(missing_docs)]
+pub struct AstCmd {
+    #[command(flatten)]
+    #[allow(missing_docs)]
+    pub input: InputArgs,
+
+    /// Show the location of each node
+    #[arg(long)]
+    pub locations: bool,
+
+    /// Only list nodes of this kind, or calls to this .command
+    #[arg(long, action = Append, value_name = "kind")]
+    pub select: Vec<String>,
+}
+
+impl From<&AstCmd> for EmblemAstDumper {
+    fn from(cmd: &AstCmd) -> Self {
+        Self::new(
+            emblem_core::ArgPath::from(cmd.input.file.clone()).into(),
+            cmd.locations,
+            cmd.select.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{arg_path::ArgPath, Args};
+
+    #[test]
+    fn input_file() {
+        assert_eq!(
+            Args::try_parse_from(["em", "ast"])
+                .unwrap()
+                .command
+                .ast()
+                .unwrap()
+                .input
+                .file,
+            ArgPath::Path("main.em".into())
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "ast", "-"])
+                .unwrap()
+                .command
+                .ast()
+                .unwrap()
+                .input
+                .file,
+            ArgPath::Stdio
+        );
+    }
+
+    #[test]
+    fn locations() {
+        assert!(
+            !Args::try_parse_from(["em", "ast"])
+                .unwrap()
+                .command
+                .ast()
+                .unwrap()
+                .locations
+        );
+        assert!(
+            Args::try_parse_from(["em", "ast", "--locations"])
+                .unwrap()
+                .command
+                .ast()
+                .unwrap()
+                .locations
+        );
+    }
+
+    #[test]
+    fn select() {
+        assert!(Args::try_parse_from(["em", "ast"])
+            .unwrap()
+            .command
+            .ast()
+            .unwrap()
+            .select
+            .is_empty());
+        assert_eq!(
+            Args::try_parse_from(["em", "ast", "--select", "word", "--select", ".h1"])
+                .unwrap()
+                .command
+                .ast()
+                .unwrap()
+                .select,
+            ["word", ".h1"]
+        );
+    }
+}
@@ -2,8 +2,9 @@ use crate::{
     arg_path::ArgPath, input_args::InputArgs, lua_args::LuaArgs, output_args::OutputArgs,
     resource_limit::ResourceLimit,
 };
-use clap::Parser;
-use emblem_core::context::DEFAULT_MAX_ITERS;
+use clap::{Parser, ValueHint::FilePath};
+use emblem_core::{build::journal::DEFAULT_JOURNAL_PATH, context::DEFAULT_MAX_ITERS};
+use std::path::PathBuf;
 
 /// Arguments to the build subcommand
 #[derive(Clone, Debug, Parser, PartialEq, Eq)]
@@ -24,6 +25,22 @@ pub struct BuildCmd {
     /// Max iterations of the typesetting loop
     #[arg(long, value_parser = ResourceLimit::<u32>::parser(), default_value_t = ResourceLimit::Limited(DEFAULT_MAX_ITERS), value_name = "max")]
     pub max_iters: ResourceLimit<u32>,
+
+    /// Report malformed constructs as errors instead of repairing them
+    #[arg(long)]
+    pub strict: bool,
+
+    /// On failure, open $EDITOR at the first error
+    #[arg(long)]
+    pub edit: bool,
+
+    /// Write a Make-compatible list of the files the output depends on
+    #[arg(long, value_name = "dep-file", value_hint = FilePath)]
+    pub dep_file: Option<PathBuf>,
+
+    /// Record build statistics in a local journal, for use with `em stats`
+    #[arg(long, value_name = "journal", value_hint = FilePath, num_args = 0..=1, require_equals = true, default_missing_value = DEFAULT_JOURNAL_PATH)]
+    pub journal: Option<PathBuf>,
 }
 
 impl BuildCmd {
@@ -40,6 +57,10 @@ impl Default for BuildCmd {
             output: Default::default(),
             lua: Default::default(),
             max_iters: ResourceLimit::Limited(DEFAULT_MAX_ITERS),
+            strict: false,
+            edit: false,
+            dep_file: None,
+            journal: None,
         }
     }
 }
@@ -48,10 +69,12 @@ impl From<&BuildCmd> for emblem_core::Builder {
     fn from(cmd: &BuildCmd) -> Self {
         let output_stem = cmd.output_stem().into();
         emblem_core::Builder::new(
-            cmd.input.file.clone().into(),
+            emblem_core::ArgPath::from(cmd.input.file.clone()).into(),
             output_stem,
             cmd.output.driver.clone(),
         )
+        .with_dep_file(cmd.dep_file.clone())
+        .with_journal(cmd.journal.clone())
     }
 }
 
@@ -59,7 +82,9 @@ impl From<&BuildCmd> for emblem_core::Builder {
 mod test {
     use super::*;
     use crate::{sandbox_level::SandboxLevel, Args};
-    use emblem_core::context::{DEFAULT_MAX_ITERS, DEFAULT_MAX_MEM, DEFAULT_MAX_STEPS};
+    use emblem_core::context::{
+        DEFAULT_MAX_ITERS, DEFAULT_MAX_MEM, DEFAULT_MAX_STEPS, DEFAULT_TAB_WIDTH,
+    };
 
     #[test]
     fn output_driver() {
@@ -85,6 +110,45 @@ mod test {
         );
     }
 
+    #[test]
+    fn driver_opts() {
+        assert_eq!(
+            Args::try_parse_from(["em"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .output
+                .driver_opts,
+            vec![]
+        );
+
+        {
+            let driver_opts = Args::try_parse_from([
+                "em",
+                "build",
+                "--driver-opt",
+                "theme=dark",
+                "--driver-opt=compress=",
+            ])
+            .unwrap()
+            .command
+            .build()
+            .unwrap()
+            .output
+            .driver_opts
+            .clone();
+            assert_eq!(driver_opts.len(), 2);
+            assert_eq!(driver_opts[0].name(), "theme");
+            assert_eq!(driver_opts[0].value(), "dark");
+            assert_eq!(driver_opts[1].name(), "compress");
+            assert_eq!(driver_opts[1].value(), "");
+        }
+
+        assert!(Args::try_parse_from(["em", "build", "--driver-opt", "theme"]).is_err());
+        assert!(Args::try_parse_from(["em", "build", "--driver-opt", "=dark"]).is_err());
+    }
+
     #[test]
     fn input_file() {
         assert_eq!(
@@ -442,4 +506,154 @@ mod test {
             ResourceLimit::Unlimited,
         );
     }
+
+    #[test]
+    fn strict() {
+        assert!(
+            !Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .strict
+        );
+        assert!(
+            Args::try_parse_from(["em", "build", "--strict"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .strict
+        );
+    }
+
+    #[test]
+    fn edit() {
+        assert!(
+            !Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .edit
+        );
+        assert!(
+            Args::try_parse_from(["em", "build", "--edit"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .edit
+        );
+    }
+
+    #[test]
+    fn tab_width() {
+        assert_eq!(
+            Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .input
+                .tab_width,
+            DEFAULT_TAB_WIDTH
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "build", "--tab-width", "8"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .input
+                .tab_width
+                .get(),
+            8
+        );
+        assert!(Args::try_parse_from(["em", "build", "--tab-width", "0"]).is_err());
+    }
+
+    #[test]
+    fn lossy_input() {
+        assert!(
+            !Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .input
+                .lossy_input
+        );
+        assert!(
+            Args::try_parse_from(["em", "build", "--lossy-input"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .input
+                .lossy_input
+        );
+    }
+
+    #[test]
+    fn dep_file() {
+        assert_eq!(
+            Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .dep_file,
+            None
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "build", "--dep-file", "main.d"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .dep_file,
+            Some(PathBuf::from("main.d"))
+        );
+    }
+
+    #[test]
+    fn journal() {
+        assert_eq!(
+            Args::try_parse_from(["em", "build"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .journal,
+            None
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "build", "--journal"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .journal,
+            Some(PathBuf::from(DEFAULT_JOURNAL_PATH))
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "build", "--journal=builds"])
+                .unwrap()
+                .command
+                .build()
+                .unwrap()
+                .journal,
+            Some(PathBuf::from("builds"))
+        );
+
+        let cmd = Args::try_parse_from(["em", "build", "--journal", "doc.em"])
+            .unwrap()
+            .command
+            .build()
+            .unwrap()
+            .clone();
+        assert_eq!(Some(PathBuf::from(DEFAULT_JOURNAL_PATH)), cmd.journal);
+        assert_eq!(ArgPath::Path("doc.em".into()), cmd.input.file);
+    }
 }
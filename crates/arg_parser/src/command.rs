@@ -1,6 +1,8 @@
 use crate::{
-    add_cmd::AddCmd, build_cmd::BuildCmd, explain_cmd::ExplainCmd, format_cmd::FormatCmd,
-    init_cmd::InitCmd, lint_cmd::LintCmd, list_cmd::ListCmd, lua_args::LuaArgs,
+    add_cmd::AddCmd, ast_cmd::AstCmd, build_cmd::BuildCmd, explain_cmd::ExplainCmd,
+    format_cmd::FormatCmd, init_cmd::InitCmd, input_args::InputArgs, lint_cmd::LintCmd,
+    list_cmd::ListCmd, lua_args::LuaArgs, output_args::OutputArgs, repl_cmd::ReplCmd,
+    stats_cmd::StatsCmd,
 };
 use clap::Subcommand;
 
@@ -11,6 +13,9 @@ pub enum Command {
     /// Add an extension the current document's compilation
     Add(AddCmd),
 
+    /// Print the parse tree of a given document
+    Ast(AstCmd),
+
     /// Build a given document
     Build(BuildCmd),
 
@@ -29,18 +34,57 @@ pub enum Command {
 
     /// Print info and exit
     List(ListCmd),
+
+    /// Start an interactive console for evaluating Lua alongside a document's extensions
+    Repl(ReplCmd),
+
+    /// Summarise the builds recorded in the build journal
+    Stats(StatsCmd),
 }
 
 impl Command {
+    pub fn input_args(&self) -> Option<&InputArgs> {
+        match self {
+            Self::Add(_) => None,
+            Self::Ast(cmd) => Some(&cmd.input),
+            Self::Build(cmd) => Some(&cmd.input),
+            Self::Explain(_) => None,
+            Self::Format(cmd) => Some(&cmd.input),
+            Self::Init(_) => None,
+            Self::Lint(cmd) => Some(&cmd.input),
+            Self::List(_) => None,
+            Self::Repl(cmd) => Some(&cmd.input),
+            Self::Stats(_) => None,
+        }
+    }
+
     pub fn lua_args(&self) -> Option<&LuaArgs> {
         match self {
             Self::Add(_) => None,
+            Self::Ast(_) => None,
             Self::Build(cmd) => Some(&cmd.lua),
             Self::Explain(_) => None,
             Self::Format(_) => None,
             Self::Init(_) => None,
             Self::Lint(cmd) => Some(&cmd.lua),
             Self::List(cmd) => Some(&cmd.lua),
+            Self::Repl(cmd) => Some(&cmd.lua),
+            Self::Stats(_) => None,
+        }
+    }
+
+    pub fn output_args(&self) -> Option<&OutputArgs> {
+        match self {
+            Self::Add(_) => None,
+            Self::Ast(_) => None,
+            Self::Build(cmd) => Some(&cmd.output),
+            Self::Explain(_) => None,
+            Self::Format(_) => None,
+            Self::Init(_) => None,
+            Self::Lint(_) => None,
+            Self::List(_) => None,
+            Self::Repl(_) => None,
+            Self::Stats(_) => None,
         }
     }
 }
@@ -54,6 +98,13 @@ impl Command {
         }
     }
 
+    pub(crate) fn ast(&self) -> Option<&AstCmd> {
+        match self {
+            Self::Ast(a) => Some(a),
+            _ => None,
+        }
+    }
+
     pub(crate) fn build(&self) -> Option<&BuildCmd> {
         match self {
             Self::Build(b) => Some(b),
@@ -94,6 +145,20 @@ impl Command {
             _ => None,
         }
     }
+
+    pub(crate) fn repl(&self) -> Option<&ReplCmd> {
+        match self {
+            Self::Repl(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn stats(&self) -> Option<&StatsCmd> {
+        match self {
+            Self::Stats(s) => Some(s),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Command {
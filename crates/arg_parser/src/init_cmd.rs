@@ -1,4 +1,4 @@
-use clap::{Parser, ValueHint::DirPath};
+use clap::{Parser, ValueEnum, ValueHint::DirPath};
 
 /// Arguments to the init subcommand
 #[derive(Clone, Debug, Parser, PartialEq, Eq)]
@@ -7,10 +7,26 @@ pub struct InitCmd {
     /// Directory to contain the new document
     #[arg(value_name = "dir", value_hint = DirPath, default_value = ".")]
     pub dir: String,
+
+    /// Kind of document to create
+    #[arg(long, value_enum, default_value_t, value_name = "template")]
+    pub template: Template,
+}
+
+/// A starting point for a new document
+#[derive(ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Template {
+    /// A short introduction to emblem.
+    #[default]
+    Basic,
+
+    /// A letter with sender and recipient address blocks.
+    Letter,
 }
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::Args;
 
     #[test]
@@ -34,4 +50,27 @@ mod test {
             "cool-doc",
         );
     }
+
+    #[test]
+    fn template() {
+        assert_eq!(
+            Args::try_parse_from(["em", "init"])
+                .unwrap()
+                .command
+                .init()
+                .unwrap()
+                .template,
+            Template::Basic,
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "init", "--template", "letter"])
+                .unwrap()
+                .command
+                .init()
+                .unwrap()
+                .template,
+            Template::Letter,
+        );
+        assert!(Args::try_parse_from(["em", "init", "--template", "novel"]).is_err());
+    }
 }
@@ -1,11 +1,31 @@
 use crate::arg_path::ArgPath;
 use clap::{Parser, ValueHint::FilePath};
+use emblem_core::context::DEFAULT_TAB_WIDTH;
+use std::num::NonZeroUsize;
 
 /// Holds the source of the user's document
-#[derive(Clone, Debug, Default, Parser, PartialEq, Eq)]
+#[derive(Clone, Debug, Parser, PartialEq, Eq)]
 #[warn(missing_docs)]
 pub struct InputArgs {
     /// Document to typeset
     #[arg(value_name = "in-file", value_hint = FilePath, default_value_t = ArgPath::default(), value_parser = ArgPath::parser())]
     pub file: ArgPath,
+
+    /// Replace invalid UTF-8 in the input rather than rejecting it
+    #[arg(long)]
+    pub lossy_input: bool,
+
+    /// Number of columns between tab stops in indentation and reported locations
+    #[arg(long, value_name = "columns", default_value_t = DEFAULT_TAB_WIDTH)]
+    pub tab_width: NonZeroUsize,
+}
+
+impl Default for InputArgs {
+    fn default() -> Self {
+        Self {
+            file: Default::default(),
+            lossy_input: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
 }
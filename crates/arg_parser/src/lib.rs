@@ -1,5 +1,6 @@
 mod add_cmd;
 mod arg_path;
+mod ast_cmd;
 mod build_cmd;
 mod command;
 mod explain_cmd;
@@ -12,16 +13,21 @@ mod list_cmd;
 mod log_args;
 mod lua_args;
 mod output_args;
+mod repl_cmd;
 mod resource_limit;
 mod sandbox_level;
+mod stats_cmd;
 
 pub use crate::add_cmd::AddCmd;
+pub use crate::ast_cmd::AstCmd;
 pub use crate::build_cmd::BuildCmd;
 pub use crate::explain_cmd::ExplainCmd;
 pub use crate::format_cmd::FormatCmd;
-pub use crate::init_cmd::InitCmd;
-pub use crate::lint_cmd::LintCmd;
+pub use crate::init_cmd::{InitCmd, Template};
+pub use crate::lint_cmd::{DashConvention, LintCmd};
 pub use crate::list_cmd::ListCmd;
+pub use crate::repl_cmd::ReplCmd;
+pub use crate::stats_cmd::StatsCmd;
 pub use command::Command;
 pub use input_args::InputArgs;
 pub use log_args::LogArgs;
@@ -65,9 +71,17 @@ impl Args {
 }
 
 impl Args {
+    pub fn input_args(&self) -> Option<&InputArgs> {
+        self.command.input_args()
+    }
+
     pub fn lua_args(&self) -> Option<&LuaArgs> {
         self.command.lua_args()
     }
+
+    pub fn output_args(&self) -> Option<&OutputArgs> {
+        self.command.output_args()
+    }
 }
 
 impl TryFrom<RawArgs> for Args {
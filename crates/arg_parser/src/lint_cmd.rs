@@ -1,6 +1,6 @@
 use crate::{input_args::InputArgs, lua_args::LuaArgs};
-use clap::Parser;
-use emblem_core::Linter as EmblemLinter;
+use clap::{Parser, ValueEnum};
+use emblem_core::{lint::DashConvention as EmblemDashConvention, Linter as EmblemLinter};
 
 /// Arguments to the lint subcommand
 #[derive(Clone, Debug, Parser, PartialEq, Eq)]
@@ -14,6 +14,10 @@ pub struct LintCmd {
     #[arg(long)]
     pub fix: bool,
 
+    /// How parenthetical dashes should be written, by default as they are first written
+    #[arg(long, value_enum, value_name = "convention")]
+    pub dashes: Option<DashConvention>,
+
     #[command(flatten)]
     #[allow(missing_docs)]
     pub lua: LuaArgs,
@@ -21,12 +25,39 @@ pub struct LintCmd {
 
 impl From<&LintCmd> for EmblemLinter {
     fn from(cmd: &LintCmd) -> Self {
-        Self::new(cmd.input.file.clone().into(), cmd.fix)
+        Self::new(
+            cmd.input.file.clone().into(),
+            cmd.fix,
+            cmd.dashes.map(Into::into),
+        )
+    }
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DashConvention {
+    /// An en dash with a space either side, ` -- `.
+    SpacedEn,
+
+    /// An em dash with a space either side, ` --- `.
+    SpacedEm,
+
+    /// An em dash with no surrounding space, `---`.
+    UnspacedEm,
+}
+
+impl From<DashConvention> for EmblemDashConvention {
+    fn from(convention: DashConvention) -> Self {
+        match convention {
+            DashConvention::SpacedEn => Self::SpacedEn,
+            DashConvention::SpacedEm => Self::SpacedEm,
+            DashConvention::UnspacedEm => Self::UnspacedEm,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::{arg_path::ArgPath, Args};
 
     #[test]
@@ -96,4 +127,29 @@ mod test {
 
         assert!(Args::try_parse_from(["em", "lint", "-a=v"]).is_err());
     }
+    #[test]
+    fn dashes() {
+        let dashes = |args: &[&str]| {
+            Args::try_parse_from(args)
+                .unwrap()
+                .command
+                .lint()
+                .unwrap()
+                .dashes
+        };
+        assert_eq!(None, dashes(&["em", "lint"]));
+        assert_eq!(
+            Some(DashConvention::SpacedEn),
+            dashes(&["em", "lint", "--dashes", "spaced-en"])
+        );
+        assert_eq!(
+            Some(DashConvention::SpacedEm),
+            dashes(&["em", "lint", "--dashes", "spaced-em"])
+        );
+        assert_eq!(
+            Some(DashConvention::UnspacedEm),
+            dashes(&["em", "lint", "--dashes=unspaced-em"])
+        );
+        assert!(Args::try_parse_from(["em", "lint", "--dashes", "unspaced-en"]).is_err());
+    }
 }
@@ -1,5 +1,5 @@
-use crate::arg_path::UninferredArgPath;
-use clap::{Parser, ValueHint::AnyPath};
+use crate::{arg_path::UninferredArgPath, ext_arg::ExtArg};
+use clap::{ArgAction::Append, Parser, ValueHint::AnyPath};
 
 /// Holds where and how the user wants their output
 #[derive(Clone, Debug, Default, Parser, PartialEq, Eq)]
@@ -12,4 +12,8 @@ pub struct OutputArgs {
     /// Override detected output format
     #[arg(short = 'T', value_name = "format")]
     pub driver: Option<String>,
+
+    /// Pass an option to the output driver
+    #[arg(long = "driver-opt", action = Append, value_parser = ExtArg::parser(), value_name = "key=value")]
+    pub driver_opts: Vec<ExtArg>,
 }
@@ -0,0 +1,75 @@
+use crate::{input_args::InputArgs, lua_args::LuaArgs};
+use clap::Parser;
+use emblem_core::Repl as EmblemRepl;
+
+/// Arguments to the repl subcommand
+#[derive(Clone, Debug, Parser, PartialEq, Eq)]
+#[warn(missing_docs)]
+pub struct ReplCmd {
+    #[command(flatten)]
+    #[allow(missing_docs)]
+    pub input: InputArgs,
+
+    #[command(flatten)]
+    #[allow(missing_docs)]
+    pub lua: LuaArgs,
+}
+
+impl From<&ReplCmd> for EmblemRepl {
+    fn from(cmd: &ReplCmd) -> Self {
+        Self::new(emblem_core::ArgPath::from(cmd.input.file.clone()).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{arg_path::ArgPath, sandbox_level::SandboxLevel, Args};
+
+    #[test]
+    fn input_file() {
+        assert_eq!(
+            Args::try_parse_from(["em", "repl"])
+                .unwrap()
+                .command
+                .repl()
+                .unwrap()
+                .input
+                .file,
+            ArgPath::Path("main.em".into())
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "repl", "doc.em"])
+                .unwrap()
+                .command
+                .repl()
+                .unwrap()
+                .input
+                .file,
+            ArgPath::Path("doc.em".into())
+        );
+    }
+
+    #[test]
+    fn sandbox_level() {
+        assert_eq!(
+            Args::try_parse_from(["em", "repl"])
+                .unwrap()
+                .command
+                .repl()
+                .unwrap()
+                .lua
+                .sandbox_level,
+            SandboxLevel::default()
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "repl", "--sandbox", "strict"])
+                .unwrap()
+                .command
+                .repl()
+                .unwrap()
+                .lua
+                .sandbox_level,
+            SandboxLevel::Strict
+        );
+    }
+}
@@ -7,7 +7,7 @@ pub enum SandboxLevel {
     Unrestricted,
 
     /// Prohibit subprocesses creation and file system access outside of the current
-    /// document's directory.
+    /// document's directory, except reading from directories the manifest declares read-only.
     #[default]
     Standard,
 
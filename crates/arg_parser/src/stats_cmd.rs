@@ -0,0 +1,70 @@
+use clap::{Parser, ValueHint::FilePath};
+use emblem_core::{build::journal::DEFAULT_JOURNAL_PATH, StatsReporter as EmblemStatsReporter};
+use std::path::PathBuf;
+
+/// Arguments to the stats subcommand
+#[derive(Clone, Debug, Parser, PartialEq, Eq)]
+#[warn(missing_docs)]
+pub struct StatsCmd {
+    /// Graph every recorded build rather than summarising them
+    #[arg(long)]
+    pub history: bool,
+
+    /// Journal to read build statistics from
+    #[arg(long, value_name = "journal", value_hint = FilePath, default_value = DEFAULT_JOURNAL_PATH)]
+    pub journal: PathBuf,
+}
+
+impl From<&StatsCmd> for EmblemStatsReporter {
+    fn from(cmd: &StatsCmd) -> Self {
+        Self::new(cmd.journal.clone(), cmd.history)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Args;
+
+    #[test]
+    fn history() {
+        assert!(
+            !Args::try_parse_from(["em", "stats"])
+                .unwrap()
+                .command
+                .stats()
+                .unwrap()
+                .history
+        );
+        assert!(
+            Args::try_parse_from(["em", "stats", "--history"])
+                .unwrap()
+                .command
+                .stats()
+                .unwrap()
+                .history
+        );
+    }
+
+    #[test]
+    fn journal() {
+        assert_eq!(
+            Args::try_parse_from(["em", "stats"])
+                .unwrap()
+                .command
+                .stats()
+                .unwrap()
+                .journal,
+            PathBuf::from(DEFAULT_JOURNAL_PATH)
+        );
+        assert_eq!(
+            Args::try_parse_from(["em", "stats", "--journal", "builds"])
+                .unwrap()
+                .command
+                .stats()
+                .unwrap()
+                .journal,
+            PathBuf::from("builds")
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use benchmarks::documents;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use emblem_core::{parser, Action, ArgPath, Builder, Context, Input, ResourceLimit};
+use std::env;
+
+// Resolution is not yet implemented, so only parsing and building are measured. A build includes
+// writing the output, which goes to a scratch file so that it does not flood the terminal.
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, doc) in documents() {
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_function(name, |b| {
+            let ctx = Context::new();
+            b.iter(|| parser::parse(ctx.alloc_file_name(name), black_box(&doc)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn typeset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typeset");
+    let output_stem = env::temp_dir().join("emblem-bench");
+    for (name, doc) in documents() {
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    Builder::new(
+                        Input::source(name, doc.clone()),
+                        ArgPath::Path(output_stem.clone()),
+                        Some("gemtext".into()),
+                    )
+                },
+                |builder| {
+                    let mut ctx = Context::new();
+                    ctx.lua_params_mut().set_max_mem(ResourceLimit::Unlimited);
+                    ctx.lua_params_mut().set_max_steps(ResourceLimit::Unlimited);
+
+                    let result = black_box(builder.run(&mut ctx));
+                    assert!(result.logs.is_empty(), "{name}: {:?}", result.logs);
+                    assert!(result.response.is_some(), "{name}: build failed");
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, typeset);
+criterion_main!(benches);
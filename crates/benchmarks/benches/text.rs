@@ -0,0 +1,46 @@
+use benchmarks::book;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use emblem_core::ast::Text;
+
+// Compares a transformation pass which only copies the text it changes against one which copies
+// every word. On the book document, about one word in fifty is changed, so the copy-on-write
+// pass leaves almost all text borrowed from the source.
+
+fn transform(c: &mut Criterion) {
+    let doc = book();
+    let words: Vec<_> = doc.split_whitespace().collect();
+
+    let mut group = c.benchmark_group("text-transform");
+    group.bench_function("copy-on-write", |b| {
+        b.iter_batched(
+            || words.iter().map(|w| Text::from(*w)).collect::<Vec<_>>(),
+            |mut texts| {
+                for text in &mut texts {
+                    if text.contains("ipsum") {
+                        *text.to_mut() = text.replace("ipsum", "IPSUM");
+                    }
+                }
+                black_box(texts)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("copy-all", |b| {
+        b.iter_batched(
+            || words.iter().map(|w| Text::from(*w)).collect::<Vec<_>>(),
+            |texts| {
+                black_box(
+                    texts
+                        .into_iter()
+                        .map(|text| Text::from(text.replace("ipsum", "IPSUM")))
+                        .collect::<Vec<_>>(),
+                )
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, transform);
+criterion_main!(benches);
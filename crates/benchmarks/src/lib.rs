@@ -0,0 +1,97 @@
+//! Representative documents used to measure the performance of emblem.
+
+/// A short document, similar to a blog post or article.
+pub fn article() -> String {
+    let mut doc = String::from(".title: A representative article\n\n");
+    for section in 1..=5 {
+        doc.push_str(&format!("## Section {section}\n\n"));
+        for _ in 0..4 {
+            doc.push_str(PARAGRAPH);
+            doc.push_str("\n\n");
+        }
+    }
+    doc
+}
+
+/// A long document with many chapters, similar to a book.
+pub fn book() -> String {
+    let mut doc = String::from(".title: A representative book\n\n");
+    for chapter in 1..=30 {
+        doc.push_str(&format!("# Chapter {chapter}\n\n"));
+        for section in 1..=8 {
+            doc.push_str(&format!("## Section {chapter}.{section}\n\n"));
+            for _ in 0..6 {
+                doc.push_str(PARAGRAPH);
+                doc.push_str("\n\n");
+            }
+        }
+    }
+    doc
+}
+
+/// A document which makes heavy use of commands, as one relying on extensions would.
+pub fn extension_heavy() -> String {
+    let mut doc = String::new();
+    for i in 0..500 {
+        doc.push_str(&format!(
+            ".set-var{{x{i}}}{{value {i}}}\n.echo{{.get-var{{x{i}}}}}: see .ref{{fig-{i}}} and .cite{{key{i}}}\n"
+        ));
+        doc.push_str(&format!(
+            ".figure[label=fig-{i}]{{image-{i}.png}}:\n\tA caption for figure {i}, with _emphasis_ and .sc{{small caps}}.\n\n"
+        ));
+    }
+    doc
+}
+
+/// A document containing deeply-nested constructs, which stress recursion in each phase.
+pub fn pathological_nesting() -> String {
+    const DEPTH: usize = 100;
+
+    let mut doc = String::new();
+    for _ in 0..10 {
+        doc.push_str(&".it{".repeat(DEPTH));
+        doc.push_str("deep");
+        doc.push_str(&"}".repeat(DEPTH));
+        doc.push_str("\n\n");
+
+        for depth in 0..DEPTH / 10 {
+            doc.push_str(&"\t".repeat(depth));
+            doc.push_str(".quote:\n");
+        }
+        doc.push_str(&"\t".repeat(DEPTH / 10));
+        doc.push_str("nested paragraph\n\n");
+    }
+    doc
+}
+
+/// All representative documents, along with their names.
+pub fn documents() -> Vec<(&'static str, String)> {
+    vec![
+        ("article", article()),
+        ("book", book()),
+        ("extension-heavy", extension_heavy()),
+        ("pathological-nesting", pathological_nesting()),
+    ]
+}
+
+const PARAGRAPH: &str =
+    "Lorem ipsum dolor sit amet, _consectetur_ adipiscing elit, sed do **eiusmod** tempor
+incididunt ut labore et dolore magna aliqua. Ut enim ad `minim` veniam, quis nostrud
+exercitation ullamco laboris---nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor
+in reprehenderit in .it{voluptate} velit esse cillum dolore eu fugiat nulla pariatur.";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use emblem_core::{parser, Context};
+
+    #[test]
+    fn documents_parse() {
+        let ctx = Context::new();
+        for (name, doc) in documents() {
+            if let Err(e) = parser::parse(ctx.alloc_file_name(name), &doc) {
+                panic!("{name} failed to parse: {e:?}");
+            }
+        }
+    }
+}
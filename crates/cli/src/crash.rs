@@ -0,0 +1,225 @@
+use emblem_core::context::{Phase, ProgressSink};
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    env, fs,
+    panic::{self, Location},
+    path::PathBuf,
+    process,
+    sync::{Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const ISSUES_URL: &str = "https://github.com/TheSignPainter98/emblem/issues";
+
+static STATE: Mutex<CrashState> = Mutex::new(CrashState::new());
+
+/// What em was doing, kept up to date so that it can be reported if em crashes.
+#[derive(Debug)]
+struct CrashState {
+    command: Option<String>,
+    file: Option<String>,
+    phase: Option<Phase>,
+    progress: Option<(u32, Option<u32>)>,
+}
+
+impl CrashState {
+    const fn new() -> Self {
+        Self {
+            command: None,
+            file: None,
+            phase: None,
+            progress: None,
+        }
+    }
+
+    /// A short description of where em was when it crashed, for example ‘typesetting main.em’.
+    fn summary(&self) -> String {
+        match (self.phase, &self.file) {
+            (Some(phase), Some(file)) => format!("{} {file}", phase_name(phase)),
+            (Some(phase), None) => phase_name(phase).into(),
+            (None, file) => {
+                let command = self.command.as_deref().unwrap_or("");
+                match file {
+                    Some(file) => format!("running ‘em {command}’ on {file}"),
+                    None => format!("running ‘em {command}’"),
+                }
+            }
+        }
+    }
+
+    fn report(&self, message: &str, panic_loc: Option<String>, backtrace: &Backtrace) -> String {
+        let mut lines = vec![
+            format!("em {} crashed", env!("CARGO_PKG_VERSION")),
+            format!(
+                "command: {}",
+                self.command.as_deref().unwrap_or("(unknown)")
+            ),
+            format!("file: {}", self.file.as_deref().unwrap_or("(none)")),
+        ];
+        lines.push(match (self.phase, self.progress) {
+            (Some(phase), Some((done, Some(total)))) => {
+                format!("phase: {} (step {done} of {total})", phase_name(phase))
+            }
+            (Some(phase), Some((done, None))) => {
+                format!("phase: {} (step {done})", phase_name(phase))
+            }
+            (Some(phase), None) => format!("phase: {}", phase_name(phase)),
+            (None, _) => "phase: (none)".into(),
+        });
+        lines.push(format!(
+            "panicked at {}: {message}",
+            panic_loc.as_deref().unwrap_or("(unknown location)")
+        ));
+        lines.push(String::new());
+        lines.push(backtrace.to_string());
+        lines.join("\n")
+    }
+}
+
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Parse => "parsing",
+        Phase::Typeset => "typesetting",
+    }
+}
+
+fn state() -> MutexGuard<'static, CrashState> {
+    // The state is only ever assigned to, so it remains valid even if a panic poisoned the lock.
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Replace the default panic handler with one which writes a crash report to a file and asks the
+/// user to report the bug, rather than printing a raw backtrace.
+pub fn install(command: &str, file: Option<String>) {
+    {
+        let mut state = state();
+        state.command = Some(command.into());
+        state.file = file;
+    }
+
+    panic::set_hook(Box::new(|info| {
+        handle_panic(info.payload(), info.location())
+    }));
+}
+
+fn handle_panic(payload: &(dyn Any + Send), panic_loc: Option<&Location<'_>>) {
+    let message = payload_message(payload);
+    let panic_loc = panic_loc.map(|loc| loc.to_string());
+    let backtrace = Backtrace::force_capture();
+
+    let state = state();
+    let report = state.report(message, panic_loc, &backtrace);
+
+    eprintln!("error: internal error while {}: {message}", state.summary());
+    match write_report(&report) {
+        Ok(path) => eprintln!(
+            "note: a crash report has been written to {}",
+            path.display()
+        ),
+        Err(e) => eprintln!("note: failed to write crash report: {e}\n\n{report}"),
+    }
+    eprintln!(
+        "help: this is a bug in em, please report it at {ISSUES_URL} and attach the crash report"
+    );
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "(no message)"
+    }
+}
+
+fn write_report(report: &str) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = env::temp_dir().join(format!("em-crash-{timestamp}-{}.txt", process::id()));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Records the progress of a build so that it can be included in crash reports.
+pub struct CrashTracker;
+
+impl ProgressSink for CrashTracker {
+    fn phase_start(&self, phase: Phase) {
+        let mut state = state();
+        state.phase = Some(phase);
+        state.progress = None;
+    }
+
+    fn phase_end(&self, _phase: Phase) {
+        let mut state = state();
+        state.phase = None;
+        state.progress = None;
+    }
+
+    fn progress(&self, _phase: Phase, done: u32, total: Option<u32>) {
+        state().progress = Some((done, total));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let mut state = CrashState {
+            command: Some("build".into()),
+            ..CrashState::new()
+        };
+        assert_eq!("running ‘em build’", state.summary());
+
+        state.file = Some("main.em".into());
+        assert_eq!("running ‘em build’ on main.em", state.summary());
+
+        state.phase = Some(Phase::Typeset);
+        assert_eq!("typesetting main.em", state.summary());
+    }
+
+    #[test]
+    fn report() {
+        let state = CrashState {
+            command: Some("build".into()),
+            file: Some("main.em".into()),
+            phase: Some(Phase::Typeset),
+            progress: Some((2, Some(5))),
+        };
+        let report = state.report(
+            "oh no",
+            Some("src/foo.rs:1:2".into()),
+            &Backtrace::disabled(),
+        );
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(
+            [
+                "command: build",
+                "file: main.em",
+                "phase: typesetting (step 2 of 5)",
+                "panicked at src/foo.rs:1:2: oh no",
+            ],
+            lines[1..5]
+        );
+
+        let report = CrashState::new().report("oh no", None, &Backtrace::disabled());
+        assert!(report.contains("phase: (none)"), "{report}");
+        assert!(
+            report.contains("panicked at (unknown location): oh no"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn payload() {
+        assert_eq!("foo", payload_message(&"foo"));
+        assert_eq!("bar", payload_message(&String::from("bar")));
+        assert_eq!("(no message)", payload_message(&1));
+    }
+}
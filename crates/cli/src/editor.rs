@@ -0,0 +1,90 @@
+use emblem_core::Log;
+use std::{
+    env,
+    io::{self, ErrorKind},
+    process::Command,
+};
+
+/// A position in a source file to open in the user's editor.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EditTarget {
+    file: String,
+    line: usize,
+}
+
+impl EditTarget {
+    /// Find the location of the first problem which caused a run to fail, if it came from a file.
+    pub fn first_failure(logs: &[Log<'_>], warnings_as_errors: bool) -> Option<Self> {
+        logs.iter()
+            .filter(|log| !log.successful(warnings_as_errors))
+            .filter_map(|log| log.srcs().first())
+            .map(|src| src.loc())
+            .find(|loc| loc.file_name() != "(stdin)")
+            .map(|loc| Self {
+                file: loc.file_name().to_string(),
+                line: loc.lines().0,
+            })
+    }
+
+    /// Open the target in the editor named by $VISUAL or $EDITOR, falling back to `vi`.
+    pub fn open(&self) -> io::Result<()> {
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".into());
+
+        let mut words = editor.split_whitespace();
+        let program = match words.next() {
+            Some(program) => program,
+            None => return Err(io::Error::new(ErrorKind::NotFound, "no editor specified")),
+        };
+
+        let status = Command::new(program)
+            .args(words)
+            .arg(format!("+{}", self.line))
+            .arg(&self.file)
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!("editor exited unsuccessfully: {status}"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use emblem_core::parser;
+
+    #[test]
+    fn first_failure() {
+        let result = parser::parse_str("broken.em", "hello\n\n#######");
+        assert_eq!(
+            Some(EditTarget {
+                file: "broken.em".into(),
+                line: 3,
+            }),
+            EditTarget::first_failure(&result.logs, false)
+        );
+    }
+
+    #[test]
+    fn no_failure() {
+        assert_eq!(None, EditTarget::first_failure(&[], false));
+        assert_eq!(None, EditTarget::first_failure(&[Log::warn("foo")], false));
+    }
+
+    #[test]
+    fn warnings_as_errors() {
+        assert_eq!(None, EditTarget::first_failure(&[Log::warn("foo")], true));
+    }
+
+    #[test]
+    fn stdin_ignored() {
+        let result = parser::parse_str("(stdin)", "#######");
+        assert_eq!(None, EditTarget::first_failure(&result.logs, false));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::Context;
 use crate::Log;
-use arg_parser::InitCmd;
+use arg_parser::{InitCmd, Template};
 use derive_new::new;
 use emblem_core::{Action, EmblemResult};
 use git2::{Error as GitError, Repository, RepositoryInitOptions};
@@ -18,19 +18,44 @@ static MAIN_CONTENTS: &str = r#"
 You have chosen, or been chosen, to relocate to one of our finest remaining typesetters.
 "#;
 
+static LETTER_CONTENTS: &str = r#"
+Your Name
+1 Example Street
+Exampletown
+
+Their Name
+2 Sample Road
+Sampleton
+
+1st January 1970
+
+Dear Their Name,
+
+Thank you for your letter.
+Write the body of your reply here.
+
+Yours sincerely,
+
+Your Name
+"#;
+
 static GITIGNORE_CONTENTS: &str = r#"
 # Output files
 *.pdf
+
+# Local build journal
+.em-journal
 "#;
 
 #[derive(new)]
 pub struct Initialiser<T: AsRef<Path>> {
     dir: T,
+    template: Template,
 }
 
 impl From<&InitCmd> for Initialiser<PathBuf> {
     fn from(cmd: &InitCmd) -> Self {
-        Self::new(PathBuf::from(cmd.dir.clone()))
+        Self::new(PathBuf::from(cmd.dir.clone()), cmd.template)
     }
 }
 
@@ -65,12 +90,20 @@ impl<T: AsRef<Path>> Initialiser<T> {
         self.init_repo()?;
 
         self.try_create_file(&git_ignore, GITIGNORE_CONTENTS)?;
-        self.try_create_file(&main_file, MAIN_CONTENTS)?;
+        self.try_create_file(&main_file, self.main_contents())?;
         self.try_create_file(&manifest_file, &self.generate_manifest()?)?;
 
         Ok(())
     }
 
+    /// The initial contents of the main file, according to the chosen template
+    fn main_contents(&self) -> &'static str {
+        match self.template {
+            Template::Basic => MAIN_CONTENTS,
+            Template::Letter => LETTER_CONTENTS,
+        }
+    }
+
     /// Construct the contents of the manifest file
     fn generate_manifest(&self) -> Result<String, Box<dyn Error>> {
         let name = self
@@ -117,7 +150,7 @@ impl<T: AsRef<Path>> Initialiser<T> {
 mod test {
     use super::*;
     use crate::manifest::DocManifest;
-    use emblem_core::{parser, EmblemResult};
+    use emblem_core::{parser, ArgPath, Builder, EmblemResult, Input};
     use std::error::Error;
     use std::{
         fs::{self, File},
@@ -126,7 +159,24 @@ mod test {
     use tempfile::TempDir;
 
     fn do_init<'em>(ctx: &'em mut Context<'em>, tmpdir: &TempDir) -> EmblemResult<'em, ()> {
-        Initialiser::new(tmpdir).run(ctx)
+        Initialiser::new(tmpdir, Template::Basic).run(ctx)
+    }
+
+    /// Build the document created in the given directory, expecting no problems.
+    fn test_build(dir: &TempDir) {
+        let mut ctx = Context::new();
+        let builder = Builder::new(
+            Input::File(dir.path().join("main.em")),
+            ArgPath::Path(dir.path().join("main.em")),
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(
+            result.logs.is_empty(),
+            "unexpected problems: {:?}",
+            result.logs
+        );
+        assert!(dir.path().join("main.gmi").exists(), "no output written");
     }
 
     fn test_files(
@@ -142,7 +192,7 @@ mod test {
         assert!(dot_gitignore.exists(), "no .gitignore");
         assert!(dot_gitignore.is_file(), ".gitignore is not a file");
 
-        const IGNORES: &[&str] = &["*.pdf"];
+        const IGNORES: &[&str] = &["*.pdf", ".em-journal"];
 
         let lines: Vec<String> = BufReader::new(File::open(dot_gitignore)?)
             .lines()
@@ -217,7 +267,36 @@ mod test {
                     .expect("tmpdir contained non-ascii characters"),
             )[1..],
         );
-        test_files(&tmpdir, &MAIN_CONTENTS[1..], &expected_manifest_contents)
+        test_files(&tmpdir, &MAIN_CONTENTS[1..], &expected_manifest_contents)?;
+        test_build(&tmpdir);
+
+        Ok(())
+    }
+
+    #[test]
+    fn letter_template() -> Result<(), Box<dyn Error>> {
+        let tmpdir = tempfile::tempdir()?;
+
+        let mut ctx = Context::new();
+        let problems = Initialiser::new(&tmpdir, Template::Letter).run(&mut ctx);
+        assert!(
+            problems.logs.is_empty(),
+            "unexpected problems: {:?}",
+            problems.logs
+        );
+
+        let found_content = fs::read_to_string(tmpdir.path().join("main.em"))?;
+        assert_eq!(&LETTER_CONTENTS[1..], found_content);
+
+        let ctx = Context::new();
+        assert!(parser::parse(
+            ctx.alloc_file_name("main.em"),
+            ctx.alloc_file(found_content)
+        )
+        .is_ok());
+        test_build(&tmpdir);
+
+        Ok(())
     }
 
     #[test]
@@ -261,7 +340,7 @@ mod test {
     fn init_repo() -> Result<(), Box<dyn Error>> {
         let dir = tempfile::tempdir()?;
 
-        let initialiser = Initialiser::new(dir.path());
+        let initialiser = Initialiser::new(dir.path(), Template::Basic);
         initialiser.init_repo()?;
 
         let dot_git = dir.path().join(".git");
@@ -2,20 +2,41 @@
 #[macro_use]
 extern crate pretty_assertions;
 
+mod crash;
+mod editor;
 mod init;
 mod manifest;
 
 pub use crate::init::Initialiser;
 use arg_parser::{Args, Command};
-use emblem_core::{log::Logger, Action, Builder, Context, Explainer, Linter, Log};
-use itertools::Itertools;
+use crash::CrashTracker;
+use editor::EditTarget;
+use emblem_core::{
+    log::Logger, Action, AstDumper, Builder, Context, Explainer, Linter, Log, Repl, StatsReporter,
+};
 use manifest::DocManifest;
-use std::{collections::HashMap, fs, process::ExitCode};
+use std::{fs, process::ExitCode};
+
+const MANIFEST_PATH: &str = "emblem.yml";
 
 fn main() -> ExitCode {
     let args = Args::parse();
 
+    crash::install(
+        command_name(&args.command),
+        args.input_args()
+            .map(|input_args| input_args.file.to_string()),
+    );
+
     let mut ctx = Context::new();
+    ctx.add_progress_sink(Box::new(CrashTracker));
+    ctx.diagnostic_params_mut()
+        .set_warnings_as_errors(args.log.warnings_as_errors);
+    if let Some(input_args) = args.input_args() {
+        let input_info = ctx.input_params_mut();
+        input_info.set_lossy(input_args.lossy_input);
+        input_info.set_tab_width(input_args.tab_width);
+    }
 
     let mut logger = Logger::new(
         args.log.verbosity.into(),
@@ -26,7 +47,7 @@ fn main() -> ExitCode {
     let raw_manifest: String;
     macro_rules! integrate_manifest {
         () => {
-            raw_manifest = match fs::read_to_string("emblem.yml") {
+            raw_manifest = match fs::read_to_string(MANIFEST_PATH) {
                 Ok(m) => m,
                 Err(e) => {
                     Log::error(e.to_string()).print(&mut logger);
@@ -43,8 +64,10 @@ fn main() -> ExitCode {
     let warnings_as_errors = args.log.warnings_as_errors;
     let (logs, successful) = match &args.command {
         Command::Add(args) => todo!("{:?}", args), // integrate_manifest!() here
+        Command::Ast(args) => execute(&mut ctx, AstDumper::from(args), warnings_as_errors),
         Command::Build(args) => {
             integrate_manifest!();
+            ctx.typesetter_params_mut().set_strict(args.strict);
             execute(&mut ctx, Builder::from(args), warnings_as_errors)
         }
         Command::Explain(args) => execute(&mut ctx, Explainer::from(args), warnings_as_errors),
@@ -52,6 +75,17 @@ fn main() -> ExitCode {
         Command::Init(args) => execute(&mut ctx, Initialiser::from(args), warnings_as_errors),
         Command::Lint(args) => execute(&mut ctx, Linter::from(args), warnings_as_errors),
         Command::List(_) => todo!(), // integrate_manifest!() here
+        Command::Repl(args) => {
+            integrate_manifest!();
+            execute(&mut ctx, Repl::from(args), warnings_as_errors)
+        }
+        Command::Stats(args) => execute(&mut ctx, StatsReporter::from(args), warnings_as_errors),
+    };
+    let edit_target = match &args.command {
+        Command::Build(args) if args.edit && !successful => {
+            EditTarget::first_failure(&logs, warnings_as_errors)
+        }
+        _ => None,
     };
     for log in logs {
         log.print(&mut logger);
@@ -59,6 +93,12 @@ fn main() -> ExitCode {
 
     logger.report();
 
+    if let Some(edit_target) = edit_target {
+        if let Err(e) = edit_target.open() {
+            Log::error(format!("failed to open editor: {e}")).print(&mut logger);
+        }
+    }
+
     if successful {
         ExitCode::SUCCESS
     } else {
@@ -66,6 +106,21 @@ fn main() -> ExitCode {
     }
 }
 
+fn command_name(command: &Command) -> &'static str {
+    match command {
+        Command::Add(_) => "add",
+        Command::Ast(_) => "ast",
+        Command::Build(_) => "build",
+        Command::Explain(_) => "explain",
+        Command::Format(_) => "fmt",
+        Command::Init(_) => "init",
+        Command::Lint(_) => "lint",
+        Command::List(_) => "list",
+        Command::Repl(_) => "repl",
+        Command::Stats(_) => "stats",
+    }
+}
+
 fn load_manifest<'ctx, 'm, 'a>(
     ctx: &'ctx mut Context<'m>,
     src: &'m str,
@@ -78,8 +133,9 @@ where
     let manifest = DocManifest::try_from(src)?;
 
     let doc_info = ctx.doc_params_mut();
+    doc_info.set_manifest_path(MANIFEST_PATH);
     doc_info.set_name(manifest.name);
-    doc_info.set_emblem_version(manifest.emblem_version.into());
+    doc_info.set_emblem_version(manifest.emblem_version);
 
     if let Some(authors) = manifest.authors {
         doc_info.set_authors(authors);
@@ -89,63 +145,78 @@ where
         doc_info.set_keywords(keywords);
     }
 
+    let driver_info = ctx.driver_params_mut();
+    if let Some(driver) = manifest.driver {
+        driver_info.set_preferred_driver(driver);
+    }
+    for (driver, options) in manifest.drivers.unwrap_or_default() {
+        driver_info.set_options(driver, options);
+    }
+    if let Some(output_args) = args.output_args() {
+        driver_info.set_overrides(
+            output_args
+                .driver_opts
+                .iter()
+                .map(|opt| (opt.name(), opt.value()))
+                .collect(),
+        );
+    }
+
+    if let Some(hooks) = manifest.hooks {
+        let hook_info = ctx.hook_params_mut();
+        hook_info.set_pre_build(hooks.pre_build.unwrap_or_default());
+        hook_info.set_post_build(hooks.post_build.unwrap_or_default());
+    }
+
     let lua_info = ctx.lua_params_mut();
+    if let Some(env) = manifest.env {
+        lua_info.set_env_allowlist(env);
+    }
+    if let Some(read_only) = manifest.read_only {
+        lua_info.set_read_only_roots(read_only);
+    }
 
-    let mut specific_args: HashMap<_, Vec<_>> = HashMap::new();
     if let Some(lua_args) = args.lua_args() {
         lua_info.set_sandbox_level(lua_args.sandbox_level.into());
         lua_info.set_max_mem(lua_args.max_mem.into());
         lua_info.set_max_steps(lua_args.max_steps.into());
 
-        let mut general_args = Vec::with_capacity(lua_args.args.len());
+        let ext_args = lua_info.args_mut();
         for arg in &lua_args.args {
-            let name = arg.name();
-
-            match name.find('.') {
-                None => general_args.push((name, arg.value())),
-                Some(0) => {
-                    return Err(Box::new(Log::error(format!(
-                        "argument module name cannot be empty: got '{}' in '{}={}'",
-                        name,
-                        name,
-                        arg.value(),
-                    ))))
-                }
-                Some(idx) => {
-                    let dep_name = &name[..idx];
-                    let arg_name = &name[1 + idx..];
-                    if let Some(args) = specific_args.get_mut(dep_name) {
-                        args.push((arg_name, arg.value()));
-                    } else {
-                        specific_args.insert(dep_name, vec![(arg_name, arg.value())]);
-                    }
-                }
-            }
+            ext_args
+                .set(arg.name(), arg.value())
+                .map_err(|e| Box::new(Log::error(e)))?;
         }
-
-        lua_info.set_general_args(general_args);
     }
 
-    let modules = manifest
+    let modules: Vec<_> = manifest
         .requires
         .unwrap_or_default()
         .into_iter()
-        .map(|(name, module)| {
-            let mut module = module.into_module(name);
-            if let Some(args) = specific_args.remove(module.rename_as().unwrap_or(name)) {
-                let dep_args = module.args_mut();
-                for (k2, v2) in args {
-                    dep_args.insert(k2, v2);
-                }
-            }
-            module
-        })
+        .map(|(name, module)| module.into_module(name))
         .collect();
 
-    if !specific_args.is_empty() {
+    // Arguments given on the command-line take precedence over those in the manifest.
+    let ext_args = lua_info.args_mut();
+    for module in &modules {
+        let ext = module.rename_as().unwrap_or(module.name());
+        for (&name, &value) in module.args() {
+            ext_args.set_default(ext, name, value);
+        }
+    }
+
+    let unused: Vec<_> = ext_args
+        .extensions()
+        .filter(|ext| {
+            !modules
+                .iter()
+                .any(|module| module.rename_as().unwrap_or(module.name()) == *ext)
+        })
+        .collect();
+    if !unused.is_empty() {
         return Err(Box::new(Log::error(format!(
             "Unused arguments: {}",
-            specific_args.keys().join(", ")
+            unused.join(", ")
         ))));
     }
 
@@ -3,18 +3,26 @@ use emblem_core::{
     context::{Module as EmblemModule, ModuleVersion as EmblemModuleVersion},
     Version as EmblemVersion,
 };
+use serde::de::{self, Deserializer as Deserialiser, Visitor};
 use serde::Deserialize as Deserialise;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::fmt;
 
 #[derive(Debug, Deserialise)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct DocManifest<'m> {
     pub name: &'m str,
-    #[serde(rename = "emblem")]
-    pub emblem_version: Version,
+    #[serde(rename = "emblem", deserialize_with = "deserialise_version")]
+    pub emblem_version: EmblemVersion,
     pub authors: Option<Vec<&'m str>>,
     pub keywords: Option<Vec<&'m str>>,
-    pub requires: Option<HashMap<&'m str, Module<'m>>>,
+    pub requires: Option<BTreeMap<&'m str, Module<'m>>>,
+    pub driver: Option<&'m str>,
+    pub drivers: Option<BTreeMap<&'m str, BTreeMap<&'m str, &'m str>>>,
+    pub hooks: Option<Hooks<'m>>,
+    pub env: Option<Vec<&'m str>>,
+    #[serde(rename = "read-only")]
+    pub read_only: Option<Vec<&'m str>>,
 }
 
 impl<'m> TryFrom<&'m str> for DocManifest<'m> {
@@ -32,36 +40,71 @@ impl<'m> TryFrom<&'m str> for DocManifest<'m> {
 
 impl<'m> DocManifest<'m> {
     fn validate(&self) -> Result<(), String> {
+        let emblem_version = self.emblem_version;
+        if !emblem_version.is_supported() {
+            return Err(format!(
+                "document targets emblem {emblem_version}, which is newer than the supported {}",
+                EmblemVersion::CURRENT
+            ));
+        }
         if let Some(requires) = &self.requires {
             for (name, ext) in requires {
                 ext.validate(name)?;
             }
         }
+        if let Some(hooks) = &self.hooks {
+            hooks.validate()?;
+        }
         Ok(())
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialise, Eq, PartialEq)]
-pub(crate) enum Version {
-    #[serde(rename = "v1.0")]
-    V1_0,
+#[derive(Debug, Default, Deserialise, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Hooks<'m> {
+    #[serde(borrow)]
+    pub pre_build: Option<Vec<Vec<&'m str>>>,
+    #[serde(borrow)]
+    pub post_build: Option<Vec<Vec<&'m str>>>,
 }
 
-impl Version {
-    #[allow(unused)]
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::V1_0 => "v1.0",
+impl<'m> Hooks<'m> {
+    fn validate(&self) -> Result<(), String> {
+        for (stage, hooks) in [
+            ("pre-build", &self.pre_build),
+            ("post-build", &self.post_build),
+        ] {
+            if let Some(hooks) = hooks {
+                if hooks.iter().any(Vec::is_empty) {
+                    return Err(format!("empty command found in {stage} hooks"));
+                }
+            }
         }
+        Ok(())
     }
 }
 
-impl From<Version> for EmblemVersion {
-    fn from(version: Version) -> Self {
-        match version {
-            Version::V1_0 => Self::V1_0,
+/// Parse the version of emblem targeted by a document, accepting any well-formed version so that
+/// unsupported ones can be reported as such.
+fn deserialise_version<'de, D>(deserialiser: D) -> Result<EmblemVersion, D::Error>
+where
+    D: Deserialiser<'de>,
+{
+    struct VersionVisitor;
+
+    impl<'de> Visitor<'de> for VersionVisitor {
+        type Value = EmblemVersion;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a version such as v1.0")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.parse().map_err(E::custom)
         }
     }
+
+    deserialiser.deserialize_str(VersionVisitor)
 }
 
 #[derive(Debug, Deserialise, Eq, PartialEq)]
@@ -71,7 +114,7 @@ pub(crate) struct Module<'m> {
     tag: Option<&'m str>,
     hash: Option<&'m str>,
     branch: Option<&'m str>,
-    args: Option<HashMap<&'m str, &'m str>>,
+    args: Option<BTreeMap<&'m str, &'m str>>,
 }
 
 impl<'m> Module<'m> {
@@ -95,7 +138,7 @@ impl<'m> Module<'m> {
     }
 
     #[allow(unused)]
-    pub fn args(&self) -> Option<&HashMap<&'m str, &'m str>> {
+    pub fn args(&self) -> Option<&BTreeMap<&'m str, &'m str>> {
         match &self.args {
             None => None,
             Some(a) => Some(a),
@@ -155,9 +198,14 @@ mod test {
         let manifest = DocManifest::try_from(&raw[..]).unwrap();
 
         assert_eq!("foo", manifest.name);
-        assert_eq!(Version::V1_0, manifest.emblem_version);
+        assert_eq!(EmblemVersion::V1_0, manifest.emblem_version);
         assert_eq!(None, manifest.authors);
         assert_eq!(None, manifest.requires);
+        assert_eq!(None, manifest.driver);
+        assert_eq!(None, manifest.drivers);
+        assert_eq!(None, manifest.hooks);
+        assert_eq!(None, manifest.env);
+        assert_eq!(None, manifest.read_only);
     }
 
     #[test]
@@ -187,6 +235,11 @@ mod test {
                     branch: dev
                   baz-hashed:
                     hash: 0123456789abcdef
+                driver: html
+                drivers:
+                  html:
+                    theme: dark
+                    toc: yes
             "#,
         );
         let manifest = DocManifest::try_from(&raw[..]).unwrap();
@@ -200,10 +253,15 @@ mod test {
             &["DARGH!", "NO!", "STAHP!", "HUEAG!"],
             manifest.keywords.unwrap().as_slice()
         );
-        assert_eq!(Version::V1_0, manifest.emblem_version);
+        assert_eq!(EmblemVersion::V1_0, manifest.emblem_version);
 
         {
             let requires = manifest.requires.unwrap();
+            assert_eq!(
+                vec!["bar-branched", "baz-hashed", "foo-tagged"],
+                requires.keys().copied().collect::<Vec<_>>(),
+                "modules not ordered by name"
+            );
             {
                 let foo_tagged = requires.get("foo-tagged").unwrap();
                 assert_eq!("qux", foo_tagged.rename_as().unwrap());
@@ -227,6 +285,81 @@ mod test {
                 );
             }
         }
+
+        assert_eq!(Some("html"), manifest.driver);
+
+        {
+            let drivers = manifest.drivers.unwrap();
+            let html = drivers.get("html").unwrap();
+            assert_eq!(&"dark", html.get("theme").unwrap());
+            assert_eq!(&"yes", html.get("toc").unwrap());
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let raw = textwrap::dedent(
+            r#"
+                name: foo
+                emblem: v1.0
+                hooks:
+                  pre-build:
+                  - [python3, gen.py, --out, data.em]
+                  - [make, figures]
+                  post-build:
+                  - [rsync, main.html, "host:www/"]
+            "#,
+        );
+        let hooks = DocManifest::try_from(&raw[..]).unwrap().hooks.unwrap();
+        assert_eq!(
+            Some(vec![
+                vec!["python3", "gen.py", "--out", "data.em"],
+                vec!["make", "figures"],
+            ]),
+            hooks.pre_build
+        );
+        assert_eq!(
+            Some(vec![vec!["rsync", "main.html", "host:www/"]]),
+            hooks.post_build
+        );
+
+        let raw = textwrap::dedent(
+            r#"
+                name: foo
+                emblem: v1.0
+                hooks:
+                  post-build:
+                  - []
+            "#,
+        );
+        let err = DocManifest::try_from(&raw[..]).unwrap_err();
+        assert_eq!("empty command found in post-build hooks", err.msg());
+    }
+
+    #[test]
+    fn env() {
+        let raw = textwrap::dedent(
+            r#"
+                name: foo
+                emblem: v1.0
+                env: [PATH, CI]
+            "#,
+        );
+        let manifest = DocManifest::try_from(&raw[..]).unwrap();
+        assert_eq!(Some(vec!["PATH", "CI"]), manifest.env);
+    }
+
+    #[test]
+    fn read_only() {
+        let raw = textwrap::dedent(
+            r#"
+                name: foo
+                emblem: v1.0
+                read-only: [/srv/assets, ../shared]
+            "#,
+        );
+        let manifest = DocManifest::try_from(&raw[..]).unwrap();
+        assert_eq!(Some(vec!["/srv/assets", "../shared"]), manifest.read_only);
     }
 
     #[test]
@@ -238,7 +371,7 @@ mod test {
             "#,
         );
         let missing_err = DocManifest::try_from(&missing[..]).unwrap_err();
-        let re = Regex::new("emblem: unknown variant `null`, expected").unwrap();
+        let re = Regex::new("emblem: invalid version 'null': expected").unwrap();
         let msg = missing_err.msg();
         assert!(
             re.is_match(msg),
@@ -252,7 +385,7 @@ mod test {
             "#,
         );
         let unknown_err = DocManifest::try_from(&unknown[..]).unwrap_err();
-        let re = Regex::new("emblem: unknown variant `UNKNOWN`, expected").unwrap();
+        let re = Regex::new("emblem: invalid version 'UNKNOWN': expected").unwrap();
         let msg = unknown_err.msg();
         assert!(
             re.is_match(msg),
@@ -260,6 +393,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn emblem_version() {
+        for (raw, expected) in [
+            ("v1.0", EmblemVersion::V1_0),
+            ("v1", EmblemVersion::V1_0),
+            ("1.0", EmblemVersion::V1_0),
+        ] {
+            let src = format!("name: foo\nemblem: {raw}\n");
+            let manifest = DocManifest::try_from(&src[..]).unwrap();
+            assert_eq!(expected, manifest.emblem_version);
+        }
+
+        for raw in ["v1.1", "v2.0"] {
+            let src = format!("name: foo\nemblem: {raw}\n");
+            let err = DocManifest::try_from(&src[..]).unwrap_err();
+            assert_eq!(
+                format!("document targets emblem {raw}, which is newer than the supported v1.0"),
+                err.msg()
+            );
+        }
+    }
+
     #[test]
     fn missing_dependency_version() {
         let raw = textwrap::dedent(
@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Build a document read from stdin and written to stdout, as in `em build - -`.
+fn build_piped(dir: &Path, src: &str) -> Output {
+    run_piped(dir, &["build", "-", "-", "-T", "gemtext"], src)
+}
+
+/// Run `em` with the given arguments, passing it the given input on stdin.
+fn run_piped(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_em"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start em");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().expect("failed to wait for em")
+}
+
+fn doc_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("emblem.yml"),
+        "name: streams\nemblem: v1.0\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn warnings_go_to_stderr() {
+    let dir = doc_dir();
+
+    let clean = build_piped(dir.path(), "hello~world\n");
+    assert!(clean.status.success(), "{clean:?}");
+    assert!(clean.stderr.is_empty(), "{clean:?}");
+    assert!(!clean.stdout.is_empty(), "{clean:?}");
+
+    let warned = build_piped(dir.path(), "hello ~ world\n");
+    assert!(warned.status.success(), "{warned:?}");
+    assert_eq!(
+        String::from_utf8_lossy(&clean.stdout),
+        String::from_utf8_lossy(&warned.stdout)
+    );
+    let stderr = String::from_utf8_lossy(&warned.stderr);
+    assert!(stderr.contains("repaired spilt glue"), "{stderr}");
+}
+
+#[test]
+fn errors_go_to_stderr() {
+    let dir = doc_dir();
+
+    let failed = build_piped(dir.path(), ".foo{\n");
+    assert!(!failed.status.success(), "{failed:?}");
+    assert!(failed.stdout.is_empty(), "{failed:?}");
+    assert!(!failed.stderr.is_empty(), "{failed:?}");
+}
+
+#[test]
+fn extension_output_goes_to_stderr() {
+    let dir = doc_dir();
+    std::fs::write(dir.path().join("main.em"), "hello\n").unwrap();
+
+    for level in ["unrestricted", "standard"] {
+        let console = run_piped(
+            dir.path(),
+            &["repl", "main.em", "--sandbox", level],
+            "print('leaked by print')\n\
+             io.write('leaked by io.write')\n\
+             io.stdout:write('leaked by io.stdout')\n",
+        );
+        assert!(console.status.success(), "{console:?}");
+
+        let stdout = String::from_utf8_lossy(&console.stdout);
+        let stderr = String::from_utf8_lossy(&console.stderr);
+        assert!(!stdout.contains("leaked"), "{level}: {stdout}");
+        for writer in ["print", "io.write", "io.stdout"] {
+            assert!(
+                stderr.contains(&format!("leaked by {writer}")),
+                "{level}: {stderr}"
+            );
+        }
+    }
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use emblem_core::parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|src: &str| {
+    let _ = parser::parse_str("fuzz.em", src);
+});
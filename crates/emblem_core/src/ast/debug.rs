@@ -1,8 +1,7 @@
-#![cfg(test)]
+#![cfg(any(test, feature = "ast-debug"))]
 use lazy_static::lazy_static;
 use regex::Regex;
 
-#[cfg(test)]
 pub trait AstDebug {
     fn test_fmt(&self, buf: &mut Vec<String>);
 
@@ -69,6 +68,7 @@ impl<T: AstDebug> AstDebug for Box<T> {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::*;
 
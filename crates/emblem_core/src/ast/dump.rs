@@ -0,0 +1,333 @@
+#![cfg(any(test, feature = "ast-debug"))]
+use crate::ast::parsed::{Content, ParsedFile, Sugar};
+use crate::ast::{AstDebug, Par, ParPart};
+use crate::context::Context;
+use crate::log::messages::Message;
+use crate::parser::{self, Location};
+use crate::{Action, EmblemResult, Input};
+use derive_new::new;
+
+/// Prints the parse tree of a document, for debugging documents and extensions.
+#[derive(new)]
+pub struct AstDumper {
+    input: Input,
+    locations: bool,
+    select: Vec<String>,
+}
+
+impl Action for AstDumper {
+    type Response = Option<String>;
+
+    fn run<'ctx>(&self, ctx: &'ctx mut Context<'ctx>) -> EmblemResult<'ctx, Self::Response> {
+        let diagnostics = ctx.diagnostic_params().clone();
+        let (logs, tree) = match parser::parse_input(ctx, &self.input) {
+            Ok((file, problems)) => {
+                let mut tree = Tree::new(self.locations, &self.select);
+                tree.file(&file);
+                (problems, Some(tree.lines.join("\n")))
+            }
+            Err(e) => (vec![e.log()], None),
+        };
+        EmblemResult::new(diagnostics.apply(logs), tree)
+    }
+
+    fn output<'ctx>(&self, resp: Self::Response) -> EmblemResult<'ctx, ()> {
+        if let Some(tree) = resp {
+            println!("{tree}");
+        }
+        EmblemResult::new(vec![], ())
+    }
+}
+
+/// Accumulates the lines of a rendered parse tree. If any selectors are given, only the matching
+/// nodes are listed, each on one line.
+struct Tree<'s> {
+    locations: bool,
+    select: &'s [String],
+    depth: usize,
+    lines: Vec<String>,
+}
+
+impl<'s> Tree<'s> {
+    fn new(locations: bool, select: &'s [String]) -> Self {
+        Self {
+            locations,
+            select,
+            depth: 0,
+            lines: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, label: impl Into<String>, loc: Option<&Location>) {
+        if !self.select.is_empty() {
+            return;
+        }
+        self.push_line(label.into(), loc);
+    }
+
+    fn push_line(&mut self, label: String, loc: Option<&Location>) {
+        let indent = "  ".repeat(self.depth);
+        let line = if self.locations {
+            let pos = loc.map(position).unwrap_or_default();
+            format!("{pos}\t{indent}{label}")
+        } else {
+            format!("{indent}{label}")
+        };
+        self.lines.push(line);
+    }
+
+    fn nested(&mut self, label: impl Into<String>, f: impl FnOnce(&mut Self)) {
+        self.push(label, None);
+        self.depth += 1;
+        f(self);
+        self.depth -= 1;
+    }
+
+    fn file(&mut self, file: &ParsedFile<'_>) {
+        self.nested("File", |tree| tree.pars(&file.pars));
+    }
+
+    fn pars(&mut self, pars: &[Par<ParPart<Content<'_>>>]) {
+        for par in pars {
+            self.nested("Par", |tree| {
+                for part in &par.parts {
+                    match part {
+                        ParPart::Line(line) => tree.nested("Line", |tree| tree.contents(line)),
+                        ParPart::Command(command) => tree.content(command),
+                    }
+                }
+            });
+        }
+    }
+
+    fn contents(&mut self, contents: &[Content<'_>]) {
+        for content in contents {
+            self.content(content);
+        }
+    }
+
+    fn content(&mut self, content: &Content<'_>) {
+        if self
+            .select
+            .iter()
+            .any(|selector| matches(selector, content))
+        {
+            self.push_line(content.repr(), Some(loc(content)));
+        }
+
+        match content {
+            Content::Command {
+                qualifier,
+                name,
+                pluses,
+                attrs,
+                inline_args,
+                remainder_arg,
+                trailer_args,
+                loc,
+                ..
+            } => {
+                let mut label = vec![".".to_owned()];
+                if let Some(qualifier) = qualifier {
+                    qualifier.surround(&mut label, "(", ")");
+                    label.push(".".into());
+                }
+                name.test_fmt(&mut label);
+                if let Some(attrs) = attrs {
+                    attrs.test_fmt(&mut label);
+                }
+                if *pluses > 0 {
+                    "+".repeat(*pluses).surround(&mut label, "(", ")");
+                }
+                self.push(label.join(""), Some(loc));
+
+                self.depth += 1;
+                for arg in inline_args {
+                    self.nested("{}", |tree| tree.contents(arg));
+                }
+                if let Some(arg) = remainder_arg {
+                    self.nested(":", |tree| tree.contents(arg));
+                }
+                for arg in trailer_args {
+                    self.nested("::", |tree| tree.pars(arg));
+                }
+                self.depth -= 1;
+            }
+            Content::Sugar(sugar) => match sugar {
+                Sugar::Italic { arg, loc, .. }
+                | Sugar::Bold { arg, loc, .. }
+                | Sugar::Monospace { arg, loc }
+                | Sugar::Smallcaps { arg, loc }
+                | Sugar::AlternateFace { arg, loc }
+                | Sugar::Heading { arg, loc, .. } => {
+                    self.push(format!("${}", sugar.call_name()), Some(loc));
+                    self.depth += 1;
+                    self.contents(arg);
+                    self.depth -= 1;
+                }
+                Sugar::Mark { loc, .. } | Sugar::Reference { loc, .. } => {
+                    self.push(content.repr(), Some(loc))
+                }
+            },
+            Content::Shebang { loc, .. }
+            | Content::Word { loc, .. }
+            | Content::Whitespace { loc, .. }
+            | Content::Dash { loc, .. }
+            | Content::Glue { loc, .. }
+            | Content::SpiltGlue { loc, .. }
+            | Content::Verbatim { loc, .. }
+            | Content::Comment { loc, .. }
+            | Content::MultiLineComment { loc, .. } => self.push(content.repr(), Some(loc)),
+        }
+    }
+}
+
+/// Returns whether a node matches the given selector. A selector is either the kind of node, for
+/// example `word` or `command`, or the name of a command prefixed with a dot, for example `.h1`.
+fn matches(selector: &str, content: &Content<'_>) -> bool {
+    if let Some(name) = selector.strip_prefix('.') {
+        return match content {
+            Content::Command { name: n, .. } => n.as_str() == name,
+            Content::Sugar(sugar) => sugar.call_name() == name,
+            _ => false,
+        };
+    }
+    kind(content) == selector
+}
+
+fn kind(content: &Content<'_>) -> &'static str {
+    match content {
+        Content::Shebang { .. } => "shebang",
+        Content::Command { .. } => "command",
+        Content::Sugar(_) => "sugar",
+        Content::Word { .. } => "word",
+        Content::Whitespace { .. } => "whitespace",
+        Content::Dash { .. } => "dash",
+        Content::Glue { .. } => "glue",
+        Content::SpiltGlue { .. } => "spilt-glue",
+        Content::Verbatim { .. } => "verbatim",
+        Content::Comment { .. } => "comment",
+        Content::MultiLineComment { .. } => "multi-line-comment",
+    }
+}
+
+fn loc<'c, 'i>(content: &'c Content<'i>) -> &'c Location<'i> {
+    match content {
+        Content::Sugar(sugar) => match sugar {
+            Sugar::Italic { loc, .. }
+            | Sugar::Bold { loc, .. }
+            | Sugar::Monospace { loc, .. }
+            | Sugar::Smallcaps { loc, .. }
+            | Sugar::AlternateFace { loc, .. }
+            | Sugar::Heading { loc, .. }
+            | Sugar::Mark { loc, .. }
+            | Sugar::Reference { loc, .. } => loc,
+        },
+        Content::Shebang { loc, .. }
+        | Content::Command { loc, .. }
+        | Content::Word { loc, .. }
+        | Content::Whitespace { loc, .. }
+        | Content::Dash { loc, .. }
+        | Content::Glue { loc, .. }
+        | Content::SpiltGlue { loc, .. }
+        | Content::Verbatim { loc, .. }
+        | Content::Comment { loc, .. }
+        | Content::MultiLineComment { loc, .. } => loc,
+    }
+}
+
+fn position(loc: &Location) -> String {
+    let (start_line, end_line) = loc.lines();
+    let (start_col, end_col) = loc.cols();
+    if start_line == end_line {
+        format!("{start_line}:{start_col}-{end_col}")
+    } else {
+        format!("{start_line}:{start_col}-{end_line}:{end_col}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dump(src: &str, locations: bool, select: &[&str]) -> String {
+        let mut ctx = Context::test_new();
+        let dumper = AstDumper::new(
+            Input::source("dump.em", src),
+            locations,
+            select.iter().map(|s| s.to_string()).collect(),
+        );
+        let result = dumper.run(&mut ctx);
+        assert!(result.logs.is_empty(), "{:?}", result.logs);
+        result.response.unwrap()
+    }
+
+    #[test]
+    fn tree() {
+        assert_eq!(
+            [
+                "File",
+                "  Par",
+                "    Line",
+                "      Word(hello)",
+                "      < >",
+                "      .it",
+                "        {}",
+                "          Word(world)",
+            ]
+            .join("\n"),
+            dump("hello .it{world}", false, &[])
+        );
+        assert_eq!(
+            [
+                "File",
+                "  Par",
+                "    Line",
+                "      $bf",
+                "        Word(foo)",
+                "  Par",
+                "    Line",
+                "      .h1",
+                "        :",
+                "          Word(bar)",
+            ]
+            .join("\n"),
+            dump("**foo**\n\n.h1: bar", false, &[])
+        );
+    }
+
+    #[test]
+    fn locations() {
+        assert_eq!(
+            [
+                "\tFile",
+                "\t  Par",
+                "\t    Line",
+                "1:1-5\t      Word(hello)",
+                "1:6-6\t      < >",
+                "1:7-11\t      Word(world)",
+            ]
+            .join("\n"),
+            dump("hello world", true, &[])
+        );
+    }
+
+    #[test]
+    fn select() {
+        assert_eq!(
+            ["Word(hello)", "Word(world)"].join("\n"),
+            dump("hello .it{world}", false, &["word"])
+        );
+        assert_eq!(
+            ".it{[Word(world)]}",
+            dump("hello .it{world}", false, &[".it"])
+        );
+        assert_eq!(
+            "1:1-5\tWord(hello)",
+            dump("hello world", true, &["word", ".it"])
+                .lines()
+                .next()
+                .unwrap()
+        );
+    }
+}
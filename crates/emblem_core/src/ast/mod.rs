@@ -1,13 +1,25 @@
 mod debug;
+mod dump;
+mod outline;
 pub mod parsed;
 mod repr_loc;
 mod text;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 pub use debug::AstDebug;
+#[cfg(any(test, feature = "ast-debug"))]
+pub use dump::AstDumper;
+pub use outline::OutlineEntry;
 pub use repr_loc::ReprLoc;
 pub use text::Text;
 
+/// Render a node in the compact format used by the parser's tests, suitable for golden-file
+/// comparisons against parse output. Enabled by the `ast-debug` feature.
+#[cfg(any(test, feature = "ast-debug"))]
+pub fn debug_repr<T: AstDebug>(node: &T) -> String {
+    node.repr()
+}
+
 #[derive(Debug)]
 pub struct File<T> {
     pub pars: Vec<Par<T>>,
@@ -75,7 +87,7 @@ impl<T> ParPart<T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl<T: AstDebug> AstDebug for File<T> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         buf.push("File".into());
@@ -83,7 +95,7 @@ impl<T: AstDebug> AstDebug for File<T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl<T: AstDebug> AstDebug for Par<T> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         buf.push("Par".into());
@@ -91,7 +103,7 @@ impl<T: AstDebug> AstDebug for Par<T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl<T: AstDebug> AstDebug for ParPart<T> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         match self {
@@ -101,7 +113,7 @@ impl<T: AstDebug> AstDebug for ParPart<T> {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Dash {
     Hyphen,
     En,
@@ -125,7 +137,7 @@ impl<T: AsRef<str>> From<T> for Dash {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Dash {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         buf.push(
@@ -161,7 +173,7 @@ impl<T: AsRef<str>> From<T> for Glue {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Glue {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         buf.push(
@@ -173,3 +185,19 @@ impl AstDebug for Glue {
         );
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::parse, FileName};
+
+    #[test]
+    fn debug_repr_matches_repr() {
+        let file = parse(FileName::new("debug-repr.em"), "hello world").unwrap();
+        assert_eq!(file.repr(), debug_repr(&file));
+        assert_eq!(
+            "File[Par[[Word(hello)|< >|Word(world)]]]",
+            debug_repr(&file)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::ast::{
+    parsed::{Content, ParsedFile, Sugar},
+    Dash, Glue, Par, ParPart,
+};
+use crate::parser::Location;
+use std::collections::HashSet;
+use std::iter::Peekable;
+
+/// A heading in a document's outline, together with the headings nested beneath it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OutlineEntry<'i> {
+    /// The plain text of the heading, without any formatting.
+    pub title: String,
+
+    /// The heading's level, from 1 to 6.
+    pub level: usize,
+
+    /// Where the heading was written.
+    pub loc: Location<'i>,
+
+    /// An identifier derived from the title which is unique within the document, suitable for
+    /// use as an anchor.
+    pub slug: String,
+
+    /// Headings which follow this one and are more deeply nested, up to the next heading which
+    /// is not.
+    pub children: Vec<OutlineEntry<'i>>,
+}
+
+impl<'i> ParsedFile<'i> {
+    /// Returns the headings in this file in document order, nested by level. Headings within the
+    /// trailer arguments of commands are included. A heading which skips levels is nested
+    /// directly beneath the previous shallower heading.
+    pub fn outline(&self) -> Vec<OutlineEntry<'i>> {
+        let mut headings = Vec::new();
+        collect_pars(&self.pars, &mut headings);
+
+        let mut slugs = HashSet::new();
+        for heading in &mut headings {
+            let base = heading.slug.clone();
+            let mut n = 1;
+            while !slugs.insert(heading.slug.clone()) {
+                heading.slug = format!("{base}-{n}");
+                n += 1;
+            }
+        }
+
+        nest(&mut headings.into_iter().peekable(), 0)
+    }
+}
+
+fn collect_pars<'i>(pars: &[Par<ParPart<Content<'i>>>], headings: &mut Vec<OutlineEntry<'i>>) {
+    for par in pars {
+        for part in &par.parts {
+            match part {
+                ParPart::Line(line) => line.iter().for_each(|c| collect(c, headings)),
+                ParPart::Command(command) => collect(command, headings),
+            }
+        }
+    }
+}
+
+fn collect<'i>(content: &Content<'i>, headings: &mut Vec<OutlineEntry<'i>>) {
+    let (level, arg, loc): (_, Vec<&[Content<'i>]>, _) = match content {
+        Content::Sugar(Sugar::Heading {
+            level,
+            arg,
+            invocation_loc,
+            ..
+        }) => (*level, vec![arg.as_slice()], invocation_loc),
+        Content::Command {
+            qualifier: None,
+            name,
+            inline_args,
+            remainder_arg,
+            invocation_loc,
+            ..
+        } if heading_level(name).is_some() => {
+            let arg = inline_args
+                .iter()
+                .chain(remainder_arg)
+                .map(Vec::as_slice)
+                .collect();
+            (heading_level(name).unwrap(), arg, invocation_loc)
+        }
+        Content::Command { trailer_args, .. } => {
+            for arg in trailer_args {
+                collect_pars(arg, headings);
+            }
+            return;
+        }
+        _ => return,
+    };
+
+    let mut title = String::new();
+    for arg in arg {
+        write_text(arg, &mut title);
+    }
+    let title = title.trim().to_owned();
+    headings.push(OutlineEntry {
+        slug: slugify(&title),
+        title,
+        level,
+        loc: loc.clone(),
+        children: Vec::new(),
+    });
+}
+
+fn heading_level(name: &str) -> Option<usize> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Write the plain text of the given content into a buffer, dropping any formatting.
+fn write_text(content: &[Content<'_>], buf: &mut String) {
+    for c in content {
+        match c {
+            Content::Word { word, .. } => buf.push_str(word),
+            Content::Whitespace { .. } => {
+                if !buf.ends_with(' ') {
+                    buf.push(' ');
+                }
+            }
+            Content::Dash { dash, .. } => buf.push_str(match dash {
+                Dash::Hyphen => "-",
+                Dash::En => "\u{2013}",
+                Dash::Em => "\u{2014}",
+            }),
+            Content::Glue { glue, .. } => {
+                if *glue == Glue::Nbsp {
+                    buf.push(' ');
+                }
+            }
+            Content::Verbatim { verbatim, .. } => buf.push_str(verbatim),
+            Content::Sugar(
+                Sugar::Italic { arg, .. }
+                | Sugar::Bold { arg, .. }
+                | Sugar::Monospace { arg, .. }
+                | Sugar::Smallcaps { arg, .. }
+                | Sugar::AlternateFace { arg, .. }
+                | Sugar::Heading { arg, .. },
+            ) => write_text(arg, buf),
+            Content::Command {
+                inline_args,
+                remainder_arg,
+                ..
+            } => {
+                for arg in inline_args.iter().chain(remainder_arg) {
+                    write_text(arg, buf);
+                }
+            }
+            Content::Sugar(Sugar::Mark { .. } | Sugar::Reference { .. })
+            | Content::Shebang { .. }
+            | Content::SpiltGlue { .. }
+            | Content::Comment { .. }
+            | Content::MultiLineComment { .. } => {}
+        }
+    }
+}
+
+/// Make an anchor-friendly identifier from a title by lowercasing it and replacing each run of
+/// characters other than letters and digits with a single hyphen.
+fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// Nest the next headings beneath one at the given level, stopping at the first heading which is
+/// not deeper.
+fn nest<'i, I>(headings: &mut Peekable<I>, level: usize) -> Vec<OutlineEntry<'i>>
+where
+    I: Iterator<Item = OutlineEntry<'i>>,
+{
+    let mut ret = Vec::new();
+    while let Some(mut heading) = headings.next_if(|h| h.level > level) {
+        heading.children = nest(headings, heading.level);
+        ret.push(heading);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser;
+    use crate::FileName;
+
+    fn outline(src: &str) -> Vec<OutlineEntry<'_>> {
+        parser::parse(FileName::new("outline.em"), src)
+            .unwrap()
+            .outline()
+    }
+
+    /// Summarise an outline as (title, level, slug, children) for comparison.
+    fn summary(entries: &[OutlineEntry<'_>]) -> Vec<(String, usize, String, usize)> {
+        let mut ret = Vec::new();
+        for entry in entries {
+            ret.push((
+                entry.title.clone(),
+                entry.level,
+                entry.slug.clone(),
+                entry.children.len(),
+            ));
+            ret.extend(summary(&entry.children));
+        }
+        ret
+    }
+
+    #[test]
+    fn nesting() {
+        let src = indoc::indoc!(
+            "
+            # Introduction
+
+            ## Background
+
+            ### Prior art
+
+            ## Motivation
+
+            # Design
+
+            ### Skipped
+
+            ## Caveats
+            "
+        );
+        let outline = outline(src);
+        assert_eq!(2, outline.len());
+        assert_eq!(
+            vec![
+                ("Introduction".into(), 1, "introduction".into(), 2),
+                ("Background".into(), 2, "background".into(), 1),
+                ("Prior art".into(), 3, "prior-art".into(), 0),
+                ("Motivation".into(), 2, "motivation".into(), 0),
+                ("Design".into(), 1, "design".into(), 2),
+                ("Skipped".into(), 3, "skipped".into(), 0),
+                ("Caveats".into(), 2, "caveats".into(), 0),
+            ],
+            summary(&outline)
+        );
+        assert_eq!((1, 1), outline[0].loc.lines());
+        assert_eq!((3, 3), outline[0].children[0].loc.lines());
+    }
+
+    #[test]
+    fn titles() {
+        let src = indoc::indoc!(
+            "
+            # The  _quick_ **brown** fox --- jumps
+
+            .h2{Commands work .tt{too}}
+
+            .h3: As do remainders
+
+            ## Marks @here are #dropped
+            "
+        );
+        assert_eq!(
+            vec![
+                (
+                    "The quick brown fox \u{2014} jumps".into(),
+                    1,
+                    "the-quick-brown-fox-jumps".into(),
+                    2
+                ),
+                ("Commands work too".into(), 2, "commands-work-too".into(), 1),
+                ("As do remainders".into(), 3, "as-do-remainders".into(), 0),
+                ("Marks are".into(), 2, "marks-are".into(), 0),
+            ],
+            summary(&outline(src))
+        );
+    }
+
+    #[test]
+    fn duplicate_slugs() {
+        let outline = outline("# Notes\n\n# Notes\n\n# Notes!\n\n# Notes 1");
+        assert_eq!(
+            vec!["notes", "notes-1", "notes-2", "notes-1-1"],
+            outline.iter().map(|e| e.slug.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn trailer_args() {
+        let outline = outline("# Top\n\n.section:\n\t## Nested\n\n\tText");
+        assert_eq!(
+            vec![
+                ("Top".into(), 1, "top".into(), 1),
+                ("Nested".into(), 2, "nested".into(), 0),
+            ],
+            summary(&outline)
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(outline("").is_empty());
+        assert!(outline("no headings here").is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::ast::{text::Text, Dash, File, Glue, Par, ParPart};
 use crate::parser::Location;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 use crate::ast::AstDebug;
 
 pub type ParsedFile<'i> = File<ParPart<Content<'i>>>;
@@ -45,6 +45,8 @@ pub enum Content<'i> {
         raw: &'i str,
         loc: Location<'i>,
     },
+    /// Text written between exclamation marks. Its content is kept exactly as written, including
+    /// any leading, trailing or repeated spaces and tabs.
     Verbatim {
         verbatim: &'i str,
         loc: Location<'i>,
@@ -59,7 +61,7 @@ pub enum Content<'i> {
     },
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Content<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         match self {
@@ -134,7 +136,7 @@ impl<'i> Attrs<'i> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Attrs<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         self.args().test_fmt(buf);
@@ -155,11 +157,11 @@ pub enum Attr<'i> {
 }
 
 impl<'i> Attr<'i> {
+    /// Construct a named attribute, falling back to an unnamed one if `raw` contains no `=`.
     pub fn named(raw: &'i str, loc: Location<'i>) -> Self {
-        Self::Named {
-            eq_idx: raw.find('=').unwrap(),
-            raw,
-            loc,
+        match raw.find('=') {
+            Some(eq_idx) => Self::Named { eq_idx, raw, loc },
+            None => Self::Unnamed { raw, loc },
         }
     }
 
@@ -198,7 +200,7 @@ impl<'i> Attr<'i> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Attr<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         match self {
@@ -265,22 +267,18 @@ impl<'i> Sugar<'i> {
             Self::Monospace { .. } => "tt",
             Self::Smallcaps { .. } => "sc",
             Self::AlternateFace { .. } => "af",
-            Self::Heading { level, .. } => match level {
-                1 => "h1",
-                2 => "h2",
-                3 => "h3",
-                4 => "h4",
-                5 => "h5",
-                6 => "h6",
-                _ => panic!("internal error: unknown heading level {level}"),
-            },
+            // The parser rejects headings outside of levels 1 to 6.
+            Self::Heading { level, .. } => {
+                const NAMES: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+                NAMES[(*level).clamp(1, NAMES.len()) - 1]
+            }
             Self::Mark { .. } => "mark",
             Self::Reference { .. } => "ref",
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl<'i> AstDebug for Sugar<'i> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         buf.push(format!("${}", self.call_name()));
@@ -321,7 +319,7 @@ impl<'i> AstDebug for Sugar<'i> {
 #[derive(Debug)]
 pub struct MultiLineComment<'i>(pub Vec<MultiLineCommentPart<'i>>);
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for MultiLineComment<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         self.0.test_fmt(buf);
@@ -335,7 +333,7 @@ pub enum MultiLineCommentPart<'i> {
     Nested(MultiLineComment<'i>),
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for MultiLineCommentPart<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         match self {
@@ -395,6 +393,17 @@ mod test {
             assert_eq!(attr.raw(), raw);
         }
 
+        #[test]
+        fn named_without_equals() {
+            let raw = "foo";
+            let p1 = Point::new(FileName::new("fname.em"), raw);
+            let attr = Attr::named(raw, Location::new(&p1, &p1.clone().shift(raw)));
+
+            assert_eq!(attr.name(), "foo");
+            assert_eq!(attr.value(), None);
+            assert_eq!(attr.raw(), raw);
+        }
+
         #[test]
         fn named() {
             let raw = " \tfoo\t =\t bar \t";
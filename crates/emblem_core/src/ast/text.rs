@@ -1,40 +1,69 @@
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 use crate::ast::AstDebug;
 use core::fmt::{self, Display, Formatter};
+use std::borrow::Cow;
+use std::ops::Deref;
 
+/// A piece of text in the document. Text taken from the source is borrowed from it, so only text
+/// which a transformation pass replaces needs its own allocation.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub enum Text<'t> {
-    Owned(String),
-    Borrowed(&'t str),
-}
+pub struct Text<'t>(Cow<'t, str>);
 
 impl<'t> Text<'t> {
-    pub fn as_str(&'t self) -> &'t str {
-        match self {
-            Text::Owned(s) => s,
-            Text::Borrowed(s) => s,
-        }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns whether this text is still borrowed from its source.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+
+    /// Get a mutable reference to this text, copying it out of its source if it is borrowed.
+    pub fn to_mut(&mut self) -> &mut String {
+        self.0.to_mut()
+    }
+
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl Deref for Text<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
     }
 }
 
 impl From<Text<'_>> for String {
     fn from(txt: Text<'_>) -> String {
-        match txt {
-            Text::Owned(s) => s,
-            Text::Borrowed(s) => s.to_string(),
-        }
+        txt.into_owned()
+    }
+}
+
+impl<'t> From<Text<'t>> for Cow<'t, str> {
+    fn from(txt: Text<'t>) -> Self {
+        txt.0
     }
 }
 
 impl<'t> From<&'t str> for Text<'t> {
     fn from(s: &'t str) -> Self {
-        Self::Borrowed(s)
+        Self(Cow::Borrowed(s))
     }
 }
 
 impl From<String> for Text<'_> {
     fn from(s: String) -> Self {
-        Self::Owned(s)
+        Self(Cow::Owned(s))
+    }
+}
+
+impl<'t> From<Cow<'t, str>> for Text<'t> {
+    fn from(s: Cow<'t, str>) -> Self {
+        Self(s)
     }
 }
 
@@ -44,9 +73,30 @@ impl Display for Text<'_> {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "ast-debug"))]
 impl AstDebug for Text<'_> {
     fn test_fmt(&self, buf: &mut Vec<String>) {
         self.as_str().test_fmt(buf);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copy_on_write() {
+        let src = "hello, world".to_owned();
+
+        let mut text = Text::from(&src[..5]);
+        assert!(text.is_borrowed());
+        assert_eq!("hello", text.as_str());
+
+        text.to_mut().push('!');
+        assert!(!text.is_borrowed());
+        assert_eq!("hello!", text.as_str());
+        assert_eq!("hello, world", src);
+
+        assert!(!Text::from(String::from("owned")).is_borrowed());
+    }
+}
@@ -0,0 +1,65 @@
+use crate::drivers::Sink;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Write a Make-style dependency file which states that `target` depends on each of `deps`. The
+/// same format is understood by Ninja.
+pub(crate) fn write(path: &Path, target: &Path, deps: &[&Path]) -> io::Result<()> {
+    let mut sink = Sink::file(path)?;
+    write!(sink, "{}:", escape(target))?;
+    for dep in deps {
+        write!(sink, " {}", escape(dep))?;
+    }
+    writeln!(sink)?;
+    sink.finish()
+}
+
+/// Escape a path so that Make reads it as a single word.
+fn escape(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut ret = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' | '#' | ':' => ret.push('\\'),
+            '$' => ret.push('$'),
+            _ => {}
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn format() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("main.d");
+
+        write(
+            &path,
+            Path::new("out/main.gmi"),
+            &[Path::new("main.em"), Path::new("emblem.yml")],
+        )?;
+        assert_eq!(
+            "out/main.gmi: main.em emblem.yml\n",
+            fs::read_to_string(&path)?
+        );
+
+        write(&path, Path::new("main.gmi"), &[])?;
+        assert_eq!("main.gmi:\n", fs::read_to_string(&path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!("main.em", escape(Path::new("main.em")));
+        assert_eq!(r"my\ doc.em", escape(Path::new("my doc.em")));
+        assert_eq!(r"\#1.em", escape(Path::new("#1.em")));
+        assert_eq!("$$HOME.em", escape(Path::new("$HOME.em")));
+        assert_eq!(r"c\:main.em", escape(Path::new("c:main.em")));
+    }
+}
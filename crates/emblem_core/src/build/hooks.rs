@@ -0,0 +1,98 @@
+use crate::context::SandboxLevel;
+use crate::extensions::exec;
+use crate::log::messages::{HookFailure, HookStage, Message};
+use crate::Log;
+
+/// Run each of the given hooks in order, stopping at the first which fails. As hooks have
+/// side-effects anywhere on the host system, they may only be run when the sandbox is
+/// unrestricted.
+pub(crate) fn run<'i>(
+    stage: HookStage,
+    hooks: &[Vec<&str>],
+    sandbox_level: SandboxLevel,
+) -> Result<(), Log<'i>> {
+    if hooks.is_empty() {
+        return Ok(());
+    }
+    if sandbox_level > SandboxLevel::Unrestricted {
+        return Err(HookFailure::new(
+            stage,
+            String::new(),
+            "hooks are unavailable to the sandbox".into(),
+        )
+        .log());
+    }
+
+    for hook in hooks {
+        let Some((program, args)) = hook.split_first() else {
+            continue;
+        };
+
+        let error = match exec::run(program, args, None) {
+            Ok(output) if output.status.success() => continue,
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                match stderr.trim_end() {
+                    "" => format!("exited with {}", output.status),
+                    stderr => format!("exited with {}\n{stderr}", output.status),
+                }
+            }
+            Err(e) => format!("failed to run: {e}"),
+        };
+        return Err(HookFailure::new(stage, hook.join(" "), error).log());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sandboxed() {
+        assert!(run(HookStage::PreBuild, &[], SandboxLevel::Strict).is_ok());
+
+        for level in [SandboxLevel::Standard, SandboxLevel::Strict] {
+            let log = run(HookStage::PreBuild, &[vec!["true"]], level).unwrap_err();
+            assert_eq!("pre-build hook failed", log.msg());
+            assert_eq!(
+                Some("try running with --sandbox unrestricted"),
+                log.help().as_deref()
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failure() {
+        let hooks = [
+            vec!["true"],
+            vec!["sh", "-c", "echo oh no >&2; exit 3"],
+            vec!["sh", "-c", "echo unreachable >&2; exit 1"],
+        ];
+        let log = run(HookStage::PostBuild, &hooks, SandboxLevel::Unrestricted).unwrap_err();
+        assert_eq!("post-build hook failed", log.msg());
+
+        let note = log.note().as_deref().unwrap();
+        assert!(
+            note.starts_with("sh -c echo oh no >&2; exit 3: exited with"),
+            "{note}"
+        );
+        assert!(note.ends_with("oh no"), "{note}");
+
+        let log = run(
+            HookStage::PreBuild,
+            &[vec!["em-no-such-program"]],
+            SandboxLevel::Unrestricted,
+        )
+        .unwrap_err();
+        let note = log.note().as_deref().unwrap();
+        assert!(
+            note.starts_with("em-no-such-program: failed to run"),
+            "{note}"
+        );
+
+        assert!(run(HookStage::PreBuild, &hooks[..1], SandboxLevel::Unrestricted).is_ok());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{BuildStats, Log};
+use annotate_snippets::snippet::AnnotationType;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where the build journal is kept if no other path is given.
+pub const DEFAULT_JOURNAL_PATH: &str = ".em-journal";
+
+/// A record of a single build. The journal is an append-only text file which never leaves the
+/// machine it is written on. It holds one tab-separated entry per line so that it can be easily
+/// inspected or processed with other tools.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JournalEntry {
+    /// When the build finished, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// How long the build took.
+    pub duration: Duration,
+
+    /// The number of warnings reported.
+    pub warnings: usize,
+
+    /// The number of errors reported.
+    pub errors: usize,
+
+    /// The size in bytes of the output, if the build wrote it to a file.
+    pub output_size: Option<u64>,
+
+    /// The approximate number of bytes in use once typesetting finished, if the build succeeded.
+    pub memory_usage: Option<usize>,
+}
+
+impl JournalEntry {
+    pub(crate) fn new(duration: Duration, logs: &[Log<'_>], stats: Option<&BuildStats>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_secs())
+            .unwrap_or_default();
+        let count =
+            |msg_type: AnnotationType| logs.iter().filter(|l| l.msg_type() == msg_type).count();
+        Self {
+            timestamp,
+            duration,
+            warnings: count(AnnotationType::Warning),
+            errors: count(AnnotationType::Error),
+            output_size: stats.and_then(|stats| stats.output_size),
+            memory_usage: stats.map(|stats| stats.approx_memory_usage),
+        }
+    }
+}
+
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t",
+            self.timestamp,
+            self.duration.as_millis(),
+            self.warnings,
+            self.errors
+        )?;
+        match self.output_size {
+            Some(size) => write!(f, "{size}\t"),
+            None => write!(f, "-\t"),
+        }?;
+        match self.memory_usage {
+            Some(usage) => write!(f, "{usage}"),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl FromStr for JournalEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid journal entry '{s}'");
+
+        let fields: Vec<_> = s.split('\t').collect();
+        // Entries written before memory usage was recorded have no final field.
+        let (timestamp, duration, warnings, errors, output_size, memory_usage) = match fields[..] {
+            [timestamp, duration, warnings, errors, output_size] => {
+                (timestamp, duration, warnings, errors, output_size, "-")
+            }
+            [timestamp, duration, warnings, errors, output_size, memory_usage] => (
+                timestamp,
+                duration,
+                warnings,
+                errors,
+                output_size,
+                memory_usage,
+            ),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            timestamp: timestamp.parse().map_err(|_| invalid())?,
+            duration: Duration::from_millis(duration.parse().map_err(|_| invalid())?),
+            warnings: warnings.parse().map_err(|_| invalid())?,
+            errors: errors.parse().map_err(|_| invalid())?,
+            output_size: match output_size {
+                "-" => None,
+                size => Some(size.parse().map_err(|_| invalid())?),
+            },
+            memory_usage: match memory_usage {
+                "-" => None,
+                usage => Some(usage.parse().map_err(|_| invalid())?),
+            },
+        })
+    }
+}
+
+/// Add an entry to the end of the journal at the given path, creating it if needed.
+pub fn append(path: &Path, entry: &JournalEntry) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{entry}")
+}
+
+/// Read all entries from the journal at the given path, oldest first.
+pub fn read(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            line.parse().map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {e}", path.display(), i + 1),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let entry = JournalEntry {
+            timestamp: 1700000000,
+            duration: Duration::from_millis(1250),
+            warnings: 2,
+            errors: 0,
+            output_size: Some(4096),
+            memory_usage: Some(65536),
+        };
+        assert_eq!("1700000000\t1250\t2\t0\t4096\t65536", entry.to_string());
+        assert_eq!(Ok(entry.clone()), entry.to_string().parse());
+
+        let entry = JournalEntry {
+            output_size: None,
+            memory_usage: None,
+            ..entry
+        };
+        assert_eq!("1700000000\t1250\t2\t0\t-\t-", entry.to_string());
+        assert_eq!(Ok(entry.clone()), entry.to_string().parse());
+        assert_eq!(Ok(entry), "1700000000\t1250\t2\t0\t-".parse());
+
+        for invalid in [
+            "",
+            "1\t2\t3\t4",
+            "1\t2\t3\t4\t5\t6\t7",
+            "1\t2\tx\t4\t5",
+            "1\t2\t3\t4\t5\tx",
+        ] {
+            assert_eq!(
+                Err(format!("invalid journal entry '{invalid}'")),
+                invalid.parse::<JournalEntry>()
+            );
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let logs = [Log::warn("foo"), Log::error("bar"), Log::warn("baz")];
+        let entry = JournalEntry::new(Duration::from_secs(1), &logs, None);
+        assert_eq!(2, entry.warnings);
+        assert_eq!(1, entry.errors);
+    }
+
+    #[test]
+    fn stats() {
+        let entry = JournalEntry::new(Duration::from_secs(1), &[], None);
+        assert_eq!(None, entry.output_size);
+        assert_eq!(None, entry.memory_usage);
+
+        let stats = BuildStats {
+            approx_memory_usage: 1234,
+            output_size: Some(56),
+        };
+        let entry = JournalEntry::new(Duration::from_secs(1), &[], Some(&stats));
+        assert_eq!(Some(56), entry.output_size);
+        assert_eq!(Some(1234), entry.memory_usage);
+    }
+
+    #[test]
+    fn append_and_read() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("journal");
+
+        let entries: Vec<_> = (1..=3)
+            .map(|i| JournalEntry {
+                timestamp: i,
+                duration: Duration::from_millis(100 * i),
+                warnings: 0,
+                errors: 0,
+                output_size: Some(i * 1000),
+                memory_usage: Some(i as usize * 10000),
+            })
+            .collect();
+        for entry in &entries {
+            append(&path, entry)?;
+        }
+        assert_eq!(entries, read(&path)?);
+
+        fs::write(&path, "1\t2\t3\n")?;
+        let err = read(&path).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err
+            .to_string()
+            .ends_with(":1: invalid journal entry '1\t2\t3'"));
+        Ok(())
+    }
+}
@@ -1,49 +1,623 @@
+mod dep_file;
+mod hooks;
+pub mod journal;
 pub(crate) mod typesetter;
 
 use crate::args::ArgPath;
-use crate::context::Context;
-use crate::log::messages::Message;
+use crate::context::{Context, Phase};
+use crate::drivers::{self, Driver, DriverOptions, Sink};
+use crate::log::messages::{ExtensionFailure, ExtensionStage, HookStage, Message};
 use crate::parser;
-use crate::path::SearchResult;
 use crate::Action;
 use crate::EmblemResult;
-use crate::Log;
+use crate::Input;
+use crate::{Doc, Log};
 use derive_new::new;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use std::{fs, io};
 
+use self::journal::JournalEntry;
 use self::typesetter::Typesetter;
 
 #[derive(new)]
 pub struct Builder {
-    input: ArgPath,
-
-    #[allow(unused)]
+    input: Input,
     output_stem: ArgPath,
-
-    #[allow(unused)]
     output_driver: Option<String>,
+    #[new(default)]
+    dep_file: Option<PathBuf>,
+    #[new(default)]
+    journal: Option<PathBuf>,
+}
+
+/// Resource usage of a build.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct BuildStats {
+    /// Approximate number of bytes held by the build context and the extension runtime once
+    /// typesetting has finished.
+    pub approx_memory_usage: usize,
+
+    /// Size in bytes of the output, if it was written to a file.
+    pub output_size: Option<u64>,
 }
 
 impl Action for Builder {
-    type Response = Option<Vec<(ArgPath, String)>>;
+    type Response = Option<BuildStats>;
 
     fn run<'ctx>(&self, ctx: &'ctx mut Context<'ctx>) -> EmblemResult<'ctx, Self::Response> {
-        let fname: SearchResult = match self.input.as_ref().try_into() {
-            Ok(f) => f,
-            Err(e) => return EmblemResult::new(vec![Log::error(e.to_string())], None),
+        let start = Instant::now();
+        let mut result = self.build(ctx);
+
+        if let Some(journal) = &self.journal {
+            let entry = JournalEntry::new(start.elapsed(), &result.logs, result.response.as_ref());
+            if let Err(e) = journal::append(journal, &entry) {
+                result.logs.push(Log::warn(format!(
+                    "failed to record build in {}: {e}",
+                    journal.display()
+                )));
+            }
+        }
+
+        result
+    }
+}
+
+impl Builder {
+    /// Also write a dependency file to the given path, listing the files on which the output
+    /// depends, for use by external build systems.
+    pub fn with_dep_file(mut self, dep_file: Option<PathBuf>) -> Self {
+        self.dep_file = dep_file;
+        self
+    }
+
+    /// Also record statistics about the build in the journal at the given path.
+    pub fn with_journal(mut self, journal: Option<PathBuf>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    fn build<'ctx>(&self, ctx: &'ctx mut Context<'ctx>) -> EmblemResult<'ctx, Option<BuildStats>> {
+        let diagnostics = ctx.diagnostic_params().clone();
+        let mut result = self.try_build(ctx);
+        result.logs = diagnostics.apply(result.logs);
+        result
+    }
+
+    fn try_build<'ctx>(
+        &self,
+        ctx: &'ctx mut Context<'ctx>,
+    ) -> EmblemResult<'ctx, Option<BuildStats>> {
+        if let Input::File(path) = &self.input {
+            let doc_dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            ctx.lua_params_mut()
+                .set_sandbox_root(doc_dir.map(Path::to_path_buf));
+        }
+
+        let drivers = drivers::drivers();
+        let driver = match drivers::infer(
+            &drivers,
+            self.output_driver.as_deref(),
+            &self.output_stem,
+            ctx.driver_params(),
+        ) {
+            Ok(driver) => driver,
+            Err(e) => return EmblemResult::new(vec![e], None),
         };
+        let driver_options =
+            match DriverOptions::parse(driver, ctx.driver_params().options_for(driver.name())) {
+                Ok(options) => options,
+                Err(problems) => return EmblemResult::new(problems, None),
+            };
+
+        if self.dep_file.is_some() && self.output_stem == ArgPath::Stdio {
+            let problem = Log::error("cannot write a dependency file for output to stdout")
+                .with_help("pass the path of the output file to write");
+            return EmblemResult::new(vec![problem], None);
+        }
 
-        let root = match parser::parse_file(ctx, fname) {
-            Ok(d) => d,
+        let sandbox_level = ctx.lua_params().sandbox_level();
+        if let Err(e) = hooks::run(
+            HookStage::PreBuild,
+            ctx.hook_params().pre_build(),
+            sandbox_level,
+        ) {
+            return EmblemResult::new(vec![e], None);
+        }
+        if let Err(e) = ctx.cancellation_token().check() {
+            return EmblemResult::new(vec![e], None);
+        }
+
+        if let Some(sink) = ctx.progress_sink() {
+            sink.phase_start(Phase::Parse);
+        }
+
+        let parsed = parser::parse_input(ctx, &self.input);
+        if let Some(sink) = ctx.progress_sink() {
+            // Imports are not yet followed, so only the root is parsed.
+            if parsed.is_ok() {
+                sink.progress(Phase::Parse, 1, None);
+            }
+            sink.phase_end(Phase::Parse);
+        }
+        let (root, mut problems) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => return EmblemResult::new(vec![e.log()], None),
         };
+        if let Err(e) = ctx.cancellation_token().check() {
+            problems.push(e);
+            return EmblemResult::new(problems, None);
+        }
 
-        let mut ext_state = ctx
-            .extension_state()
-            .expect("internal error: failed to create Lua state");
+        let mut ext_state = match ctx.extension_state() {
+            Ok(ext_state) => ext_state,
+            Err(e) => {
+                problems.push(match ctx.cancellation_token().check() {
+                    Err(cancelled) => cancelled,
+                    Ok(()) => ExtensionFailure::new(ExtensionStage::Setup, e.to_string()).log(),
+                });
+                return EmblemResult::new(problems, None);
+            }
+        };
 
         let typesetter = Typesetter::new(ctx, &mut ext_state);
-        typesetter.typeset(root).unwrap();
+        let doc = match typesetter.typeset(root) {
+            Ok((doc, typeset_problems)) => {
+                problems.extend(typeset_problems);
+                doc
+            }
+            Err(e) => {
+                problems.push(match ctx.cancellation_token().check() {
+                    Err(cancelled) => cancelled,
+                    Ok(()) => ExtensionFailure::new(ExtensionStage::Typeset, e.to_string()).log(),
+                });
+                return EmblemResult::new(problems, None);
+            }
+        };
+
+        if let Err(e) = ctx.cancellation_token().check() {
+            problems.push(e);
+            return EmblemResult::new(problems, None);
+        }
+
+        // Overrides decide which problems are errors, and so whether the output is written.
+        if !ctx.diagnostic_params().successful(&problems) {
+            return EmblemResult::new(problems, None);
+        }
+
+        let output_path = drivers::output_path(&self.output_stem, driver);
+        if let Err(e) = Self::write_output(driver, &driver_options, &doc, &output_path) {
+            problems.push(Log::error(format!("failed to write {output_path}: {e}")));
+            return EmblemResult::new(problems, None);
+        }
+
+        if let (Some(dep_file), ArgPath::Path(target)) = (&self.dep_file, &output_path) {
+            if let Err(e) = self.write_dep_file(ctx, dep_file, target) {
+                problems.push(e);
+                return EmblemResult::new(problems, None);
+            }
+        }
+
+        if let Err(e) = hooks::run(
+            HookStage::PostBuild,
+            ctx.hook_params().post_build(),
+            sandbox_level,
+        ) {
+            problems.push(e);
+        }
+
+        let output_size = match &output_path {
+            ArgPath::Path(path) => fs::metadata(path).ok().map(|m| m.len()),
+            ArgPath::Stdio => None,
+        };
+        let stats = BuildStats {
+            approx_memory_usage: ctx.approx_memory_usage() + ext_state.lua().used_memory(),
+            output_size,
+        };
+        EmblemResult::new(problems, Some(stats))
+    }
+
+    fn write_output(
+        driver: &dyn Driver,
+        options: &DriverOptions<'_>,
+        doc: &Doc<'_>,
+        path: &ArgPath,
+    ) -> io::Result<()> {
+        let mut sink = Sink::create(path)?;
+        driver.write(doc, options, &mut sink)?;
+        sink.finish()
+    }
+
+    fn write_dep_file<'i>(
+        &self,
+        ctx: &Context<'_>,
+        dep_file: &Path,
+        target: &Path,
+    ) -> Result<(), Log<'i>> {
+        let mut deps = Vec::with_capacity(2);
+        if let Input::File(input) = &self.input {
+            deps.push(input.as_path());
+        }
+        if let Some(manifest) = ctx.doc_params().manifest_path() {
+            deps.push(Path::new(manifest));
+        }
+
+        dep_file::write(dep_file, target, &deps).map_err(|e| {
+            Log::error(format!(
+                "failed to write dependency file {}: {e}",
+                dep_file.display()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::{CancellationToken, SandboxLevel, Severity};
+    use crate::ResourceLimit;
+    use annotate_snippets::snippet::AnnotationType;
+
+    #[test]
+    fn extension_failure() {
+        let mut ctx = Context::test_new();
+        ctx.lua_params_mut().set_max_mem(ResourceLimit::Limited(1));
+
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Stdio,
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+
+        let log = &result.logs[0];
+        assert_eq!("failed to start the extension runtime", log.msg());
+        assert_eq!(
+            Some("try raising the memory limit with --max-mem"),
+            log.help().as_deref()
+        );
+    }
+
+    #[test]
+    fn cancellation() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut ctx = Context::test_new();
+        let token = CancellationToken::new();
+        ctx.set_cancellation_token(token.clone());
+        token.cancel();
+
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Path(dir.path().join("main.em")),
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+        assert_eq!("build cancelled", result.logs[0].msg());
+        assert!(!dir.path().join("main.gmi").exists());
+    }
+
+    #[test]
+    fn progress_events() {
+        use crate::context::{Phase, ProgressSink};
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Recorder {
+            events: Rc<RefCell<Vec<String>>>,
+        }
+
+        impl ProgressSink for Recorder {
+            fn phase_start(&self, phase: Phase) {
+                self.events.borrow_mut().push(format!("start {phase:?}"));
+            }
+
+            fn phase_end(&self, phase: Phase) {
+                self.events.borrow_mut().push(format!("end {phase:?}"));
+            }
+
+            fn progress(&self, phase: Phase, done: u32, total: Option<u32>) {
+                self.events
+                    .borrow_mut()
+                    .push(format!("{phase:?} {done}/{total:?}"));
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut ctx = Context::test_new();
+        ctx.add_progress_sink(Box::new(Recorder {
+            events: events.clone(),
+        }));
+
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Path(dir.path().join("main.em")),
+            Some("gemtext".into()),
+        );
+        builder.run(&mut ctx);
+        assert_eq!(
+            *events.borrow(),
+            [
+                "start Parse",
+                "Parse 1/None",
+                "end Parse",
+                "start Typeset",
+                "Typeset 1/None",
+                "end Typeset",
+            ]
+        );
+    }
+
+    #[test]
+    fn severity_overrides() {
+        for (severity, expected) in [
+            (None, Some(AnnotationType::Warning)),
+            (Some(Severity::Allow), None),
+            (Some(Severity::Deny), Some(AnnotationType::Error)),
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+
+            let mut ctx = Context::test_new();
+            if let Some(severity) = severity {
+                ctx.diagnostic_params_mut().set_severity("E014", severity);
+            }
+
+            let builder = Builder::new(
+                Input::source("main.em", "hello ~ world"),
+                ArgPath::Path(dir.path().join("main.em")),
+                Some("gemtext".into()),
+            );
+            let result = builder.run(&mut ctx);
+            assert_eq!(
+                expected,
+                result.logs.first().map(|log| log.msg_type()),
+                "{:?}",
+                result.logs
+            );
+        }
+    }
+
+    #[test]
+    fn errors_stop_output() {
+        for strict in [false, true] {
+            let dir = tempfile::tempdir().unwrap();
+
+            let mut ctx = Context::test_new();
+            ctx.typesetter_params_mut().set_strict(strict);
+
+            let builder = Builder::new(
+                Input::source("main.em", "hello ~ world"),
+                ArgPath::Path(dir.path().join("main.em")),
+                Some("gemtext".into()),
+            );
+            let result = builder.run(&mut ctx);
+            assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+            assert_eq!(!strict, result.logs[0].successful(false));
+            assert_eq!(!strict, result.response.is_some());
+            assert_eq!(!strict, dir.path().join("main.gmi").exists());
+        }
+    }
+
+    #[test]
+    fn warnings_stop_output() {
+        for warnings_as_errors in [false, true] {
+            let dir = tempfile::tempdir().unwrap();
+            let dep_file = dir.path().join("main.d");
+
+            let mut ctx = Context::test_new();
+            ctx.diagnostic_params_mut()
+                .set_warnings_as_errors(warnings_as_errors);
+
+            let builder = Builder::new(
+                Input::source("main.em", "hello ~ world"),
+                ArgPath::Path(dir.path().join("main.em")),
+                Some("gemtext".into()),
+            )
+            .with_dep_file(Some(dep_file.clone()));
+            let result = builder.run(&mut ctx);
+            assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+            assert_eq!(AnnotationType::Warning, result.logs[0].msg_type());
+            assert_eq!(!warnings_as_errors, result.response.is_some());
+            assert_eq!(!warnings_as_errors, dir.path().join("main.gmi").exists());
+            assert_eq!(!warnings_as_errors, dep_file.exists());
+        }
+    }
+
+    #[test]
+    fn driver_options() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut ctx = Context::test_new();
+        ctx.driver_params_mut()
+            .set_overrides(vec![("compact", "true"), ("theme", "dark")]);
+
+        let builder = Builder::new(
+            Input::source("main.em", "# Hello\n\nworld"),
+            ArgPath::Path(dir.path().join("main.em")),
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+        assert_eq!(
+            "unknown option ‘theme’ for driver ‘gemtext’",
+            result.logs[0].msg()
+        );
+        assert!(!dir.path().join("main.gmi").exists());
+    }
+
+    #[test]
+    fn compact_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut ctx = Context::test_new();
+        ctx.driver_params_mut()
+            .set_options("gemtext", [("compact", "true")].into_iter().collect());
+
+        let builder = Builder::new(
+            Input::source("main.em", "# Hello\n\nworld"),
+            ArgPath::Path(dir.path().join("main.em")),
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.logs.is_empty(), "{:?}", result.logs);
+
+        let written = std::fs::read_to_string(dir.path().join("main.gmi")).unwrap();
+        assert_eq!("# Hello\nworld\n", written);
+    }
+
+    #[test]
+    fn write_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut ctx = Context::test_new();
+        ctx.driver_params_mut().set_default_driver("gemtext");
+
+        let builder = Builder::new(
+            Input::source("main.em", "# Hello\n\nworld"),
+            ArgPath::Path(dir.path().join("main.em")),
+            None,
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.logs.is_empty(), "{:?}", result.logs);
+        assert!(result.response.is_some());
+
+        let written = std::fs::read_to_string(dir.path().join("main.gmi")).unwrap();
+        assert_eq!("# Hello\n\nworld\n", written);
+    }
+
+    #[test]
+    fn dep_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("main.em");
+        std::fs::write(&input, "hello").unwrap();
+        let dep_file = dir.path().join("main.d");
+
+        let mut ctx = Context::test_new();
+        ctx.doc_params_mut().set_manifest_path("emblem.yml");
+
+        let builder = Builder::new(
+            Input::File(input.clone()),
+            ArgPath::Path(input.clone()),
+            Some("gemtext".into()),
+        )
+        .with_dep_file(Some(dep_file.clone()));
+        let result = builder.run(&mut ctx);
+        assert!(result.logs.is_empty(), "{:?}", result.logs);
+
+        assert_eq!(
+            format!(
+                "{}: {} emblem.yml\n",
+                dir.path().join("main.gmi").display(),
+                input.display()
+            ),
+            std::fs::read_to_string(&dep_file).unwrap()
+        );
+
+        let mut ctx = Context::test_new();
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Stdio,
+            Some("gemtext".into()),
+        )
+        .with_dep_file(Some(dep_file));
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+        assert_eq!(
+            "cannot write a dependency file for output to stdout",
+            result.logs[0].msg()
+        );
+    }
+
+    #[test]
+    fn journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("journal");
+
+        for _ in 0..2 {
+            let mut ctx = Context::test_new();
+            let builder = Builder::new(
+                Input::source("main.em", "hello"),
+                ArgPath::Path(dir.path().join("main.em")),
+                Some("gemtext".into()),
+            )
+            .with_journal(Some(journal.clone()));
+            let result = builder.run(&mut ctx);
+            assert!(result.logs.is_empty(), "{:?}", result.logs);
+            assert_eq!(Some(6), result.response.unwrap().output_size);
+        }
+
+        let mut ctx = Context::test_new();
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Stdio,
+            Some("no-such-driver".into()),
+        )
+        .with_journal(Some(journal.clone()));
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+
+        let entries = journal::read(&journal).unwrap();
+        assert_eq!(3, entries.len());
+        assert_eq!(
+            vec![Some(6), Some(6), None],
+            entries.iter().map(|e| e.output_size).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 0, 1],
+            entries.iter().map(|e| e.errors).collect::<Vec<_>>()
+        );
+        assert!(entries[..2]
+            .iter()
+            .all(|e| e.memory_usage > Some(Context::test_new().approx_memory_usage())));
+        assert_eq!(None, entries[2].memory_usage);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("main.gmi");
+        let output = output.to_str().unwrap();
+        let marker = dir.path().join("marker");
+        let marker = marker.to_str().unwrap();
+
+        let mut ctx = Context::test_new();
+        ctx.lua_params_mut()
+            .set_sandbox_level(SandboxLevel::Unrestricted);
+        ctx.driver_params_mut().set_default_driver("gemtext");
+        ctx.hook_params_mut()
+            .set_pre_build(vec![vec!["touch", marker]]);
+        ctx.hook_params_mut()
+            .set_post_build(vec![vec!["test", "-f", output], vec!["rm", marker]]);
+
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Path(dir.path().join("main.em")),
+            None,
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.logs.is_empty(), "{:?}", result.logs);
+        assert!(!dir.path().join("marker").exists());
+
+        let mut ctx = Context::test_new();
+        ctx.lua_params_mut()
+            .set_sandbox_level(SandboxLevel::Unrestricted);
+        ctx.hook_params_mut().set_pre_build(vec![vec!["false"]]);
 
-        EmblemResult::new(vec![], Some(vec![]))
+        let builder = Builder::new(
+            Input::source("main.em", "hello"),
+            ArgPath::Stdio,
+            Some("gemtext".into()),
+        );
+        let result = builder.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+        assert_eq!("pre-build hook failed", result.logs[0].msg());
     }
 }
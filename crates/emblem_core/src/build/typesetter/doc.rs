@@ -3,8 +3,13 @@ use crate::{
         parsed::{Attr, Attrs, Content, ParsedFile, Sugar},
         Dash, Glue, Par, ParPart, ReprLoc, Text,
     },
+    log::{
+        messages::{Construct, IgnoredConstruct, Message, SpiltGlueRepair},
+        Log,
+    },
     parser::Location,
 };
+use std::{cell::RefCell, rc::Rc};
 
 #[cfg(test)]
 use crate::ast::AstDebug;
@@ -106,41 +111,89 @@ impl<'em> AstDebug for Doc<'em> {
 }
 
 #[derive(Clone)]
-struct DocStackState {
+struct DocStackState<'em> {
     discern_pars: bool,
+    strict: bool,
+    problems: Rc<RefCell<Vec<Log<'em>>>>,
 }
 
-impl DocStackState {
-    pub fn new() -> Self {
-        Self { discern_pars: true }
+impl<'em> DocStackState<'em> {
+    pub fn new(strict: bool) -> Self {
+        Self {
+            discern_pars: true,
+            strict,
+            problems: Rc::default(),
+        }
     }
 
     pub fn with_discern_pars(&self, discern_pars: bool) -> Self {
-        Self { discern_pars }
+        Self {
+            discern_pars,
+            strict: self.strict,
+            problems: self.problems.clone(),
+        }
+    }
+
+    fn report(&self, problem: Log<'em>) {
+        self.problems.borrow_mut().push(problem);
+    }
+
+    /// Resolve glue which is separated from the text either side by whitespace. If the glue sits
+    /// between two pieces of text, it is repaired into regular glue, unless in strict mode.
+    fn resolve_spilt_glue(
+        &self,
+        raw: &'em str,
+        loc: Location<'em>,
+        repairable: bool,
+    ) -> Option<DocElem<'em>> {
+        let glue = raw.trim_matches(' ');
+        if self.strict {
+            self.report(SpiltGlueRepair::new(loc, glue, true).log());
+            return None;
+        }
+        if !repairable {
+            self.report(IgnoredConstruct::new(loc, Construct::SpiltGlue).log());
+            return None;
+        }
+
+        self.report(SpiltGlueRepair::new(loc.clone(), glue, false).log());
+        Some(DocElem::Glue {
+            glue: Glue::from(glue),
+            loc,
+        })
+    }
+}
+
+impl<'em> Doc<'em> {
+    /// Convert a parsed file into a document, returning problems with any content which had to be
+    /// repaired or dropped. In strict mode, content is never repaired.
+    pub fn from_parsed(parsed: ParsedFile<'em>, strict: bool) -> (Self, Vec<Log<'em>>) {
+        let state = DocStackState::new(strict);
+        let problems = state.problems.clone();
+        let doc = parsed.into_doc(state).unwrap_or_default().simplify();
+        let problems = problems.take();
+        (doc, problems)
     }
 }
 
 impl<'i> From<ParsedFile<'i>> for Doc<'i> {
     fn from(parsed: ParsedFile<'i>) -> Self {
-        parsed
-            .into_doc(DocStackState::new())
-            .unwrap_or_default()
-            .simplify()
+        Self::from_parsed(parsed, false).0
     }
 }
 
 trait IntoDoc<'em> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>>;
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>>;
 }
 
 impl<'em> IntoDoc<'em> for ParsedFile<'em> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         self.pars.into_doc(state)
     }
 }
 
 impl<'em> IntoDoc<'em> for Vec<Par<ParPart<Content<'em>>>> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         let content: Vec<_> = self
             .into_iter()
             .flat_map(|par| {
@@ -198,7 +251,7 @@ impl<'em> IntoDoc<'em> for Vec<Par<ParPart<Content<'em>>>> {
 }
 
 impl<'em> IntoDoc<'em> for Par<ParPart<Content<'em>>> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         Some(DocElem::Content(
             self.parts
                 .into_iter()
@@ -210,7 +263,7 @@ impl<'em> IntoDoc<'em> for Par<ParPart<Content<'em>>> {
 }
 
 impl<'em> IntoDoc<'em> for ParPart<Content<'em>> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         match self {
             Self::Line(l) => l.into_doc(state),
             Self::Command(c) => c.into_doc(state),
@@ -219,17 +272,38 @@ impl<'em> IntoDoc<'em> for ParPart<Content<'em>> {
 }
 
 impl<'em> IntoDoc<'em> for Vec<Content<'em>> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
+        let joinable: Vec<_> = self.iter().map(is_joinable).collect();
+        let between_joinables =
+            |i: usize| i > 0 && joinable[i - 1] && joinable.get(i + 1).copied().unwrap_or(false);
         Some(DocElem::Content(
             self.into_iter()
-                .filter_map(|c| c.into_doc(state.clone()))
+                .enumerate()
+                .filter_map(|(i, c)| match c {
+                    Content::SpiltGlue { raw, loc } => {
+                        state.resolve_spilt_glue(raw, loc, between_joinables(i))
+                    }
+                    c => c.into_doc(state.clone()),
+                })
                 .collect(),
         ))
     }
 }
 
+/// Returns whether glue may join the given content to its neighbour.
+fn is_joinable(content: &Content<'_>) -> bool {
+    matches!(
+        content,
+        Content::Command { .. }
+            | Content::Sugar(_)
+            | Content::Word { .. }
+            | Content::Dash { .. }
+            | Content::Verbatim { .. }
+    )
+}
+
 impl<'em> IntoDoc<'em> for Content<'em> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         match self {
             Self::Command {
                 name,
@@ -267,9 +341,9 @@ impl<'em> IntoDoc<'em> for Content<'em> {
                 word: Text::from(verbatim),
                 loc,
             }),
+            Self::SpiltGlue { raw, loc } => state.resolve_spilt_glue(raw, loc, false),
             Self::Shebang { .. }
             | Self::Whitespace { .. }
-            | Self::SpiltGlue { .. }
             | Self::Comment { .. }
             | Self::MultiLineComment { .. } => None,
         }
@@ -277,7 +351,7 @@ impl<'em> IntoDoc<'em> for Content<'em> {
 }
 
 impl<'em> IntoDoc<'em> for Sugar<'em> {
-    fn into_doc(self, state: DocStackState) -> Option<DocElem<'em>> {
+    fn into_doc(self, state: DocStackState<'em>) -> Option<DocElem<'em>> {
         Some({
             let name = Text::from(self.call_name());
             let loc = self.repr_loc();
@@ -369,6 +443,72 @@ mod test {
         );
     }
 
+    fn typeset_problems(input: &str, strict: bool) -> (String, Vec<String>) {
+        let ctx = Context::new();
+        let parsed = parser::parse(
+            ctx.alloc_file_name("problems.em"),
+            ctx.alloc_file(input.into()),
+        )
+        .unwrap();
+        let (doc, problems) = Doc::from_parsed(parsed, strict);
+        for problem in &problems {
+            problem.assert_compliant();
+        }
+        (
+            doc.repr(),
+            problems.iter().map(|p| p.msg().to_owned()).collect(),
+        )
+    }
+
+    #[test]
+    fn ignored_constructs() {
+        let (repr, problems) = typeset_problems("foo ~\nbar\n\n~ baz", false);
+        assert_eq!("[.p{[Word(foo)|Word(bar)]}|.p{Word(baz)}]", repr);
+        assert_eq!(vec!["ignored spilt glue"; 2], problems);
+    }
+
+    #[test]
+    fn repaired_spilt_glue() {
+        let (repr, problems) = typeset_problems("foo ~bar\n\n.baz: qux~ quux", false);
+        assert_eq!(
+            "[.p{[Word(foo)|~|Word(bar)]}|.baz{[Word(qux)|~|Word(quux)]}]",
+            repr
+        );
+        assert_eq!(vec!["repaired spilt glue"; 2], problems);
+
+        for (input, expected) in [
+            ("a ~b", ".p{[Word(a)|~|Word(b)]}"),
+            ("a~ b", ".p{[Word(a)|~|Word(b)]}"),
+            ("a ~ b", ".p{[Word(a)|~|Word(b)]}"),
+            ("a  ~  b", ".p{[Word(a)|~|Word(b)]}"),
+            ("a ~~b", ".p{[Word(a)|~~|Word(b)]}"),
+            ("a~~ b", ".p{[Word(a)|~~|Word(b)]}"),
+            ("a ~~ b", ".p{[Word(a)|~~|Word(b)]}"),
+            ("_a_ ~ `b`", ".p{[.it{Word(a)}|~|.tt{Word(b)}]}"),
+        ] {
+            let (repr, problems) = typeset_problems(input, false);
+            assert_eq!(expected, repr, "{input:?}");
+            assert_eq!(vec!["repaired spilt glue"], problems, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn strict_spilt_glue() {
+        for (input, expected) in [
+            ("a ~b", ".p{[Word(a)|Word(b)]}"),
+            ("a~~ b", ".p{[Word(a)|Word(b)]}"),
+            ("a ~\nb", ".p{[Word(a)|Word(b)]}"),
+        ] {
+            let (repr, problems) = typeset_problems(input, true);
+            assert_eq!(expected, repr, "{input:?}");
+            assert_eq!(vec!["spilt glue is not allowed"], problems, "{input:?}");
+        }
+
+        let (repr, problems) = typeset_problems("a~b", true);
+        assert_eq!(".p{[Word(a)|~|Word(b)]}", repr);
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+
     #[test]
     fn into_doc_headings() {
         assert_structure(
@@ -464,6 +604,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn into_doc_verbatim() {
+        assert_structure("verbatim", "!foo!", ".p{Word(foo)}");
+        assert_structure(
+            "verbatim-whitespace",
+            "!  foo \t bar\t!",
+            r".p{Word(  foo \t bar\t)}",
+        );
+        assert_structure(
+            "verbatim-in-code",
+            "`!let  x\t= 1 !`",
+            r".tt{Word(let  x\t= 1 )}",
+        );
+    }
+
     #[test]
     fn into_doc_comments() {
         assert_structure("line-comment", "// on this final night", "[]");
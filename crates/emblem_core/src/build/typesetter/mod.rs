@@ -3,32 +3,63 @@ use std::error::Error;
 use crate::{
     ast::parsed::ParsedFile,
     build::typesetter::doc::Doc,
+    context::{CancellationToken, Phase, ProgressSink},
     extensions::{Event, ExtensionState},
-    Context, ResourceLimit,
+    Context, Log, ResourceLimit,
 };
 
 pub(crate) mod doc;
 
 // TODO(kcza): typesettable file -> [fragment]
 
-pub struct Typesetter<'em> {
-    ext_state: &'em mut ExtensionState<'em>,
+pub struct Typesetter<'em, 'ext> {
+    ext_state: &'ext mut ExtensionState<'em>,
     curr_iter: u32,
     max_iters: ResourceLimit<u32>,
+    strict: bool,
+    progress_sink: Option<&'em (dyn ProgressSink + 'em)>,
+    cancellation_token: &'em CancellationToken,
 }
 
-impl<'em> Typesetter<'em> {
-    pub fn new(ctx: &'em Context<'em>, ext_state: &'em mut ExtensionState<'em>) -> Self {
+impl<'em, 'ext> Typesetter<'em, 'ext> {
+    pub fn new(ctx: &'em Context<'em>, ext_state: &'ext mut ExtensionState<'em>) -> Self {
         Self {
             ext_state,
             curr_iter: 0,
             max_iters: ctx.typesetter_params().max_iters(),
+            strict: ctx.typesetter_params().strict(),
+            progress_sink: ctx.progress_sink(),
+            cancellation_token: ctx.cancellation_token(),
         }
     }
 
-    pub fn typeset(mut self, root: ParsedFile<'em>) -> Result<(), Box<dyn Error>> {
-        let mut root = Doc::from(root);
+    pub fn typeset(
+        mut self,
+        root: ParsedFile<'em>,
+    ) -> Result<(Doc<'em>, Vec<Log<'em>>), Box<dyn Error>> {
+        if let Some(sink) = self.progress_sink {
+            sink.phase_start(Phase::Typeset);
+        }
+
+        let progress_sink = self.progress_sink;
+        let result = self.typeset_doc(root);
+
+        if let Some(sink) = progress_sink {
+            sink.phase_end(Phase::Typeset);
+        }
+
+        result
+    }
+
+    fn typeset_doc(
+        &mut self,
+        root: ParsedFile<'em>,
+    ) -> Result<(Doc<'em>, Vec<Log<'em>>), Box<dyn Error>> {
+        let (mut root, problems) = Doc::from_parsed(root, self.strict);
         loop {
+            if self.cancellation_token.is_cancelled() {
+                return Err("build cancelled".into());
+            }
             self.iter(&mut root)?;
 
             if !self.will_reiter() {
@@ -41,7 +72,7 @@ impl<'em> Typesetter<'em> {
             final_iter: self.curr_iter,
         })?;
 
-        Ok(())
+        Ok((root, problems))
     }
 
     fn will_reiter(&self) -> bool {
@@ -56,8 +87,6 @@ impl<'em> Typesetter<'em> {
     fn iter(&mut self, _root: &mut Doc<'em>) -> Result<(), Box<dyn Error>> {
         self.curr_iter += 1;
 
-        println!("Doing iteration {} of {:?}", self.curr_iter, self.max_iters);
-
         self.ext_state.handle(Event::IterStart {
             iter: self.curr_iter,
         })?;
@@ -66,6 +95,11 @@ impl<'em> Typesetter<'em> {
             iter: self.curr_iter,
         })?;
 
+        if let Some(sink) = self.progress_sink {
+            // The iteration limit only bounds the number of passes, so the total is unknown.
+            sink.progress(Phase::Typeset, self.curr_iter, None);
+        }
+
         Ok(())
     }
 }
@@ -206,6 +240,134 @@ mod test {
         Ok(())
     }
 
+    #[derive(Debug, PartialEq, Eq)]
+    enum Recorded {
+        Start(Phase),
+        End(Phase),
+        Progress(Phase, u32, Option<u32>),
+    }
+
+    struct Recorder {
+        events: Rc<RefCell<Vec<Recorded>>>,
+    }
+
+    impl ProgressSink for Recorder {
+        fn phase_start(&self, phase: Phase) {
+            self.events.borrow_mut().push(Recorded::Start(phase));
+        }
+
+        fn phase_end(&self, phase: Phase) {
+            self.events.borrow_mut().push(Recorded::End(phase));
+        }
+
+        fn progress(&self, phase: Phase, done: u32, total: Option<u32>) {
+            self.events
+                .borrow_mut()
+                .push(Recorded::Progress(phase, done, total));
+        }
+    }
+
+    #[test]
+    fn progress_events() -> Result<(), Box<dyn Error>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let ctx = {
+            let mut ctx = Context::test_new();
+            ctx.typesetter_params_mut()
+                .set_max_iters(ResourceLimit::Limited(2));
+            ctx.add_progress_sink(Box::new(Recorder {
+                events: events.clone(),
+            }));
+            ctx
+        };
+        let mut ext_state = ctx.extension_state()?;
+        ext_state.add_listener(
+            EventType::IterEnd,
+            Value::Function(ext_state.lua().create_function(move |lua, ()| {
+                lua.app_data_mut::<ExtensionData>()
+                    .unwrap()
+                    .request_reiter();
+                Ok(Value::Nil)
+            })?),
+        )?;
+
+        Typesetter::new(&ctx, &mut ext_state).typeset(
+            parser::parse(
+                ctx.alloc_file_name("progress_events.em"),
+                ctx.alloc_file("".into()),
+            )
+            .unwrap(),
+        )?;
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                Recorded::Start(Phase::Typeset),
+                Recorded::Progress(Phase::Typeset, 1, None),
+                Recorded::Progress(Phase::Typeset, 2, None),
+                Recorded::End(Phase::Typeset),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cancellation() -> Result<(), Box<dyn Error>> {
+        let token = CancellationToken::new();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let iter_end_indices = Rc::new(RefCell::new(Vec::new()));
+        let iter_end_indices_clone = iter_end_indices.clone();
+
+        let ctx = {
+            let mut ctx = Context::test_new();
+            ctx.typesetter_params_mut()
+                .set_max_iters(ResourceLimit::Limited(5));
+            ctx.set_cancellation_token(token.clone());
+            ctx.add_progress_sink(Box::new(Recorder {
+                events: events.clone(),
+            }));
+            ctx
+        };
+        let mut ext_state = ctx.extension_state()?;
+        ext_state.add_listener(
+            EventType::IterEnd,
+            Value::Function(ext_state.lua().create_function(move |lua, event: Table| {
+                let n: Integer = event.get("iter")?;
+                iter_end_indices_clone.try_borrow_mut().unwrap().push(n);
+
+                lua.app_data_mut::<ExtensionData>()
+                    .unwrap()
+                    .request_reiter();
+                if n == 2 {
+                    token.cancel();
+                }
+
+                Ok(Value::Nil)
+            })?),
+        )?;
+
+        let result = Typesetter::new(&ctx, &mut ext_state).typeset(
+            parser::parse(
+                ctx.alloc_file_name("cancellation.em"),
+                ctx.alloc_file("".into()),
+            )
+            .unwrap(),
+        );
+        assert_eq!("build cancelled", result.unwrap_err().to_string());
+        assert_eq!(iter_end_indices.borrow().clone(), [1, 2]);
+        assert_eq!(
+            *events.borrow(),
+            [
+                Recorded::Start(Phase::Typeset),
+                Recorded::Progress(Phase::Typeset, 1, None),
+                Recorded::Progress(Phase::Typeset, 2, None),
+                Recorded::End(Phase::Typeset),
+            ]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn event_listeners() -> Result<(), Box<dyn Error>> {
         struct Callable {
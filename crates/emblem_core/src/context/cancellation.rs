@@ -0,0 +1,53 @@
+use crate::Log;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag which allows a running build to be abandoned, for example when new edits make it stale.
+/// Clones share the same flag, so one may be kept by whatever decides to cancel the build while
+/// the other is given to the build's context. The build stops at the next point it checks the
+/// flag: between stages, between typesetting iterations, before each hook and periodically while
+/// running Lua code.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request that any build using this token stop as soon as possible.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error if the build has been cancelled.
+    pub(crate) fn check<'i>(&self) -> Result<(), Log<'i>> {
+        if self.is_cancelled() {
+            return Err(Log::error("build cancelled"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        assert!(clone.check().is_ok());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!("build cancelled", token.check().unwrap_err().msg());
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Arguments passed to extensions. An argument may be addressed to a particular extension by
+/// qualifying its name as `ext.name`, otherwise it is general and seen by all extensions.
+#[derive(Debug, Default)]
+pub struct ExtensionArgs<'m> {
+    general: BTreeMap<&'m str, ArgValue<'m>>,
+    sections: BTreeMap<&'m str, BTreeMap<&'m str, ArgValue<'m>>>,
+}
+
+impl<'m> ExtensionArgs<'m> {
+    /// Set the argument with the given, possibly-qualified, name, replacing any previous value.
+    pub fn set(&mut self, name: &'m str, value: &'m str) -> Result<(), String> {
+        let (ext, arg_name) = match name.split_once('.') {
+            None => (None, name),
+            Some(("", _)) => {
+                return Err(format!(
+                    "argument module name cannot be empty: got '{name}' in '{name}={value}'"
+                ))
+            }
+            Some((ext, arg_name)) => (Some(ext), arg_name),
+        };
+        if arg_name.is_empty() {
+            return Err(format!(
+                "argument name cannot be empty: got '{name}' in '{name}={value}'"
+            ));
+        }
+
+        let value = ArgValue::parse(value);
+        match ext {
+            None => self.general.insert(arg_name, value),
+            Some(ext) => self
+                .sections
+                .entry(ext)
+                .or_default()
+                .insert(arg_name, value),
+        };
+        Ok(())
+    }
+
+    /// Set an argument for the given extension, unless a value has already been set for it.
+    pub fn set_default(&mut self, ext: &'m str, name: &'m str, value: &'m str) {
+        self.sections
+            .entry(ext)
+            .or_default()
+            .entry(name)
+            .or_insert_with(|| ArgValue::parse(value));
+    }
+
+    /// Get the value of an argument as seen by the given extension. Arguments addressed to the
+    /// extension take precedence over general ones.
+    pub fn get(&self, ext: &str, name: &str) -> Option<ArgValue<'m>> {
+        self.sections
+            .get(ext)
+            .and_then(|section| section.get(name))
+            .or_else(|| self.general.get(name))
+            .copied()
+    }
+
+    pub fn general(&self) -> &BTreeMap<&'m str, ArgValue<'m>> {
+        &self.general
+    }
+
+    pub fn section(&self, ext: &str) -> Option<&BTreeMap<&'m str, ArgValue<'m>>> {
+        self.sections.get(ext)
+    }
+
+    /// Returns the names of the extensions which have arguments addressed to them, in order.
+    pub fn extensions(&self) -> impl Iterator<Item = &'m str> + '_ {
+        self.sections.keys().copied()
+    }
+}
+
+/// The value of an extension argument. Its type is inferred from the text given.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArgValue<'m> {
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(&'m str),
+}
+
+impl<'m> ArgValue<'m> {
+    /// Infer the type of a raw value. Text which would not be written back the same way, such as
+    /// `007`, is kept as a string.
+    pub fn parse(raw: &'m str) -> Self {
+        match raw {
+            "true" => return Self::Bool(true),
+            "false" => return Self::Bool(false),
+            _ => {}
+        }
+        if let Ok(i) = raw.parse::<i64>() {
+            if i.to_string() == raw {
+                return Self::Integer(i);
+            }
+        }
+        if let Ok(n) = raw.parse::<f64>() {
+            if n.is_finite() && n.to_string() == raw {
+                return Self::Number(n);
+            }
+        }
+        Self::String(raw)
+    }
+}
+
+impl Display for ArgValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => b.fmt(f),
+            Self::Integer(i) => i.fmt(f),
+            Self::Number(n) => n.fmt(f),
+            Self::String(s) => s.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn values() {
+        assert_eq!(ArgValue::Bool(true), ArgValue::parse("true"));
+        assert_eq!(ArgValue::Bool(false), ArgValue::parse("false"));
+        assert_eq!(ArgValue::Integer(-12), ArgValue::parse("-12"));
+        assert_eq!(ArgValue::Number(1.5), ArgValue::parse("1.5"));
+        for raw in ["", "yes", "007", "+1", "1.50", "inf", "NaN", "1e3"] {
+            assert_eq!(ArgValue::String(raw), ArgValue::parse(raw), "{raw:?}");
+        }
+
+        for raw in ["true", "-12", "1.5", "hello"] {
+            assert_eq!(raw, ArgValue::parse(raw).to_string());
+        }
+    }
+
+    #[test]
+    fn sections() {
+        let mut args = ExtensionArgs::default();
+        args.set("colour", "red").unwrap();
+        args.set("size", "1").unwrap();
+        args.set("toc.depth", "2").unwrap();
+        args.set("toc.size", "3").unwrap();
+
+        assert_eq!(Some(ArgValue::String("red")), args.get("toc", "colour"));
+        assert_eq!(Some(ArgValue::Integer(3)), args.get("toc", "size"));
+        assert_eq!(Some(ArgValue::Integer(1)), args.get("maths", "size"));
+        assert_eq!(None, args.get("maths", "depth"));
+
+        assert_eq!(
+            vec!["colour", "size"],
+            args.general().keys().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(2, args.section("toc").unwrap().len());
+        assert_eq!(None, args.section("maths"));
+        assert_eq!(vec!["toc"], args.extensions().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn defaults() {
+        let mut args = ExtensionArgs::default();
+        args.set("toc.depth", "2").unwrap();
+        args.set_default("toc", "depth", "5");
+        args.set_default("toc", "numbered", "true");
+
+        assert_eq!(Some(ArgValue::Integer(2)), args.get("toc", "depth"));
+        assert_eq!(Some(ArgValue::Bool(true)), args.get("toc", "numbered"));
+    }
+
+    #[test]
+    fn invalid_names() {
+        let mut args = ExtensionArgs::default();
+        assert_eq!(
+            Err("argument module name cannot be empty: got '.depth' in '.depth=2'".into()),
+            args.set(".depth", "2")
+        );
+        assert_eq!(
+            Err("argument name cannot be empty: got 'toc.' in 'toc.=2'".into()),
+            args.set("toc.", "2")
+        );
+        assert_eq!(None, args.section(""));
+        assert_eq!(None, args.section("toc"));
+    }
+}
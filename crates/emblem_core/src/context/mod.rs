@@ -1,24 +1,48 @@
+mod cancellation;
+mod ext_args;
 pub(crate) mod file_name;
 mod module;
+mod progress;
 
-use crate::{ExtensionState, FileName, Typesetter, Version};
+use crate::{ExtensionState, FileName, Log, Typesetter, Version};
+use annotate_snippets::snippet::AnnotationType;
+pub use cancellation::CancellationToken;
 use derive_new::new;
+pub use ext_args::{ArgValue, ExtensionArgs};
 use mlua::Result as MLuaResult;
 pub use module::{Module, ModuleVersion};
 use num::{Bounded, Integer};
+use progress::ProgressSinks;
+pub use progress::{Phase, ProgressSink};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use typed_arena::Arena;
 
 pub const DEFAULT_MAX_STEPS: u32 = 100_000;
 pub const DEFAULT_MAX_MEM: usize = 100_000;
 pub const DEFAULT_MAX_ITERS: u32 = 5;
+pub const DEFAULT_TAB_WIDTH: NonZeroUsize = match NonZeroUsize::new(4) {
+    Some(width) => width,
+    None => unreachable!(),
+};
 
 #[derive(Default)]
 pub struct Context<'m> {
     files: Arena<String>,
+    file_bytes: Cell<usize>,
     doc_params: DocumentParameters<'m>,
+    input_params: InputParameters,
     lua_params: LuaParameters<'m>,
     typesetter_params: TypesetterParameters,
+    diagnostic_params: DiagnosticParameters<'m>,
+    driver_params: DriverParameters<'m>,
+    hook_params: HookParameters<'m>,
+    progress_sinks: ProgressSinks<'m>,
+    cancellation_token: CancellationToken,
 }
 
 impl<'m> Context<'m> {
@@ -26,14 +50,31 @@ impl<'m> Context<'m> {
         Self::default()
     }
 
+    /// Create a context whose file arena initially has space for `chunk_size` files. Each time the
+    /// arena fills, it allocates a new chunk at least as large as all those before it.
+    pub fn with_arena_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            files: Arena::with_capacity(chunk_size),
+            ..Self::default()
+        }
+    }
+
     pub fn alloc_file_name(&self, name: &str) -> FileName {
         FileName::new(name)
     }
 
     pub fn alloc_file(&self, content: String) -> &str {
+        self.file_bytes
+            .set(self.file_bytes.get() + content.capacity());
         self.files.alloc(content)
     }
 
+    /// Estimate the number of bytes held by this context, including the source of all files it
+    /// has loaded. Memory used by extensions is not included.
+    pub fn approx_memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.files.len() * mem::size_of::<String>() + self.file_bytes.get()
+    }
+
     pub fn doc_params(&self) -> &DocumentParameters<'m> {
         &self.doc_params
     }
@@ -42,6 +83,14 @@ impl<'m> Context<'m> {
         &mut self.doc_params
     }
 
+    pub fn input_params(&self) -> &InputParameters {
+        &self.input_params
+    }
+
+    pub fn input_params_mut(&mut self) -> &mut InputParameters {
+        &mut self.input_params
+    }
+
     pub fn lua_params(&self) -> &LuaParameters<'m> {
         &self.lua_params
     }
@@ -58,11 +107,58 @@ impl<'m> Context<'m> {
         &mut self.typesetter_params
     }
 
+    pub fn diagnostic_params(&self) -> &DiagnosticParameters<'m> {
+        &self.diagnostic_params
+    }
+
+    pub fn diagnostic_params_mut(&mut self) -> &mut DiagnosticParameters<'m> {
+        &mut self.diagnostic_params
+    }
+
+    pub fn driver_params(&self) -> &DriverParameters<'m> {
+        &self.driver_params
+    }
+
+    pub fn driver_params_mut(&mut self) -> &mut DriverParameters<'m> {
+        &mut self.driver_params
+    }
+
+    pub fn hook_params(&self) -> &HookParameters<'m> {
+        &self.hook_params
+    }
+
+    pub fn hook_params_mut(&mut self) -> &mut HookParameters<'m> {
+        &mut self.hook_params
+    }
+
+    /// Add a sink to receive progress events. Every sink added receives every event.
+    pub fn add_progress_sink(&mut self, progress_sink: Box<dyn ProgressSink + 'm>) {
+        self.progress_sinks.push(progress_sink);
+    }
+
+    pub fn progress_sink(&self) -> Option<&(dyn ProgressSink + 'm)> {
+        if self.progress_sinks.is_empty() {
+            return None;
+        }
+        Some(&self.progress_sinks)
+    }
+
+    pub fn set_cancellation_token(&mut self, cancellation_token: CancellationToken) {
+        self.cancellation_token = cancellation_token;
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
     pub fn extension_state(&'m self) -> MLuaResult<ExtensionState<'m>> {
         ExtensionState::new(self)
     }
 
-    pub fn typesetter(&'m self, ext_state: &'m mut ExtensionState<'m>) -> Typesetter<'m> {
+    pub fn typesetter<'ext>(
+        &'m self,
+        ext_state: &'ext mut ExtensionState<'m>,
+    ) -> Typesetter<'m, 'ext> {
         Typesetter::new(self, ext_state)
     }
 }
@@ -72,15 +168,23 @@ impl<'m> Context<'m> {
     pub fn test_new() -> Self {
         Self {
             files: Arena::new(),
+            file_bytes: Cell::new(0),
             doc_params: DocumentParameters::test_new(),
+            input_params: InputParameters::default(),
             lua_params: LuaParameters::test_new(),
             typesetter_params: TypesetterParameters::test_new(),
+            diagnostic_params: DiagnosticParameters::default(),
+            driver_params: DriverParameters::default(),
+            hook_params: HookParameters::default(),
+            progress_sinks: ProgressSinks::default(),
+            cancellation_token: CancellationToken::default(),
         }
     }
 }
 
 #[derive(Debug, Default)]
 pub struct DocumentParameters<'m> {
+    manifest_path: Option<&'m str>,
     name: Option<&'m str>,
     emblem_version: Option<Version>,
     authors: Option<Vec<&'m str>>,
@@ -88,6 +192,15 @@ pub struct DocumentParameters<'m> {
 }
 
 impl<'m> DocumentParameters<'m> {
+    /// Set the path of the manifest which describes this document.
+    pub fn set_manifest_path(&mut self, manifest_path: &'m str) {
+        self.manifest_path = Some(manifest_path);
+    }
+
+    pub fn manifest_path(&self) -> Option<&'m str> {
+        self.manifest_path
+    }
+
     pub fn set_name(&mut self, name: &'m str) {
         self.name = Some(name);
     }
@@ -128,6 +241,7 @@ impl<'m> DocumentParameters<'m> {
 impl<'m> DocumentParameters<'m> {
     pub fn test_new() -> Self {
         Self {
+            manifest_path: None,
             name: Some("On the Origin of Burnt Toast"),
             emblem_version: Some(Version::V1_0),
             authors: Some(vec!["kcza"]),
@@ -141,8 +255,11 @@ pub struct LuaParameters<'m> {
     sandbox_level: SandboxLevel,
     max_mem: ResourceLimit<usize>,
     max_steps: ResourceLimit<u32>,
-    general_args: Option<Vec<(&'m str, &'m str)>>,
+    args: ExtensionArgs<'m>,
     modules: Vec<Module<'m>>,
+    env_allowlist: Vec<&'m str>,
+    read_only_roots: Vec<&'m str>,
+    sandbox_root: Option<PathBuf>,
 }
 
 impl<'m> Default for LuaParameters<'m> {
@@ -151,8 +268,11 @@ impl<'m> Default for LuaParameters<'m> {
             sandbox_level: Default::default(),
             max_mem: ResourceLimit::Limited(DEFAULT_MAX_MEM),
             max_steps: ResourceLimit::Limited(DEFAULT_MAX_STEPS),
-            general_args: Default::default(),
+            args: Default::default(),
             modules: Default::default(),
+            env_allowlist: Default::default(),
+            read_only_roots: Default::default(),
+            sandbox_root: None,
         }
     }
 }
@@ -182,14 +302,16 @@ impl<'m> LuaParameters<'m> {
         self.max_steps
     }
 
-    pub fn set_general_args(&mut self, general_args: Vec<(&'m str, &'m str)>) {
-        self.general_args = Some(general_args);
+    pub fn args(&self) -> &ExtensionArgs<'m> {
+        &self.args
     }
 
-    pub fn general_args(&self) -> &Option<Vec<(&'m str, &'m str)>> {
-        &self.general_args
+    pub fn args_mut(&mut self) -> &mut ExtensionArgs<'m> {
+        &mut self.args
     }
 
+    /// Set the modules to load. Modules are loaded in the given order, so for reproducible builds
+    /// this order must not depend on hashing.
     pub fn set_modules(&mut self, modules: Vec<Module<'m>>) {
         self.modules = modules;
     }
@@ -197,6 +319,35 @@ impl<'m> LuaParameters<'m> {
     pub fn modules(&self) -> &[Module<'m>] {
         &self.modules
     }
+
+    /// Set the environment variables which extensions may read when sandboxed.
+    pub fn set_env_allowlist(&mut self, env_allowlist: Vec<&'m str>) {
+        self.env_allowlist = env_allowlist;
+    }
+
+    pub fn env_allowlist(&self) -> &[&'m str] {
+        &self.env_allowlist
+    }
+
+    /// Set the directories outside of the document's own which extensions may read from when
+    /// sandboxed.
+    pub fn set_read_only_roots(&mut self, read_only_roots: Vec<&'m str>) {
+        self.read_only_roots = read_only_roots;
+    }
+
+    pub fn read_only_roots(&self) -> &[&'m str] {
+        &self.read_only_roots
+    }
+
+    /// Set the directory which extensions may read and write when sandboxed, usually the one
+    /// containing the document. If unset, the current directory is used.
+    pub fn set_sandbox_root(&mut self, sandbox_root: Option<PathBuf>) {
+        self.sandbox_root = sandbox_root;
+    }
+
+    pub fn sandbox_root(&self) -> Option<&Path> {
+        self.sandbox_root.as_deref()
+    }
 }
 
 #[cfg(test)]
@@ -206,8 +357,11 @@ impl<'m> LuaParameters<'m> {
             sandbox_level: SandboxLevel::Strict,
             max_mem: ResourceLimit::Unlimited,
             max_steps: ResourceLimit::Unlimited,
-            general_args: None,
+            args: ExtensionArgs::default(),
             modules: vec![],
+            env_allowlist: vec![],
+            read_only_roots: vec![],
+            sandbox_root: None,
         }
     }
 }
@@ -217,7 +371,8 @@ pub enum SandboxLevel {
     /// Side-effects allowed anywhere on host system
     Unrestricted,
 
-    /// Side-effects allowed within this document's folder only
+    /// Side-effects allowed within this document's folder only, though files may also be read
+    /// from any declared read-only directories
     #[default]
     Standard,
 
@@ -237,14 +392,51 @@ impl SandboxLevel {
     }
 }
 
+#[derive(Debug)]
+pub struct InputParameters {
+    lossy: bool,
+    tab_width: NonZeroUsize,
+}
+
+impl Default for InputParameters {
+    fn default() -> Self {
+        Self {
+            lossy: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+}
+
+impl InputParameters {
+    /// Whether invalid UTF-8 in input should be replaced rather than rejected.
+    pub fn lossy(&self) -> bool {
+        self.lossy
+    }
+
+    pub fn set_lossy(&mut self, lossy: bool) {
+        self.lossy = lossy
+    }
+
+    /// The number of columns each tab occupies, both in indentation and in reported locations.
+    pub fn tab_width(&self) -> NonZeroUsize {
+        self.tab_width
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: NonZeroUsize) {
+        self.tab_width = tab_width
+    }
+}
+
 pub struct TypesetterParameters {
     max_iters: ResourceLimit<u32>,
+    strict: bool,
 }
 
 impl Default for TypesetterParameters {
     fn default() -> Self {
         Self {
             max_iters: ResourceLimit::Limited(DEFAULT_MAX_ITERS),
+            strict: false,
         }
     }
 }
@@ -257,6 +449,15 @@ impl TypesetterParameters {
     pub fn set_max_iters(&mut self, max_iters: ResourceLimit<u32>) {
         self.max_iters = max_iters
     }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set whether malformed constructs which would otherwise be repaired are treated as errors.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict
+    }
 }
 
 #[cfg(test)]
@@ -264,10 +465,148 @@ impl TypesetterParameters {
     pub fn test_new() -> Self {
         Self {
             max_iters: ResourceLimit::Unlimited,
+            strict: false,
         }
     }
 }
 
+#[derive(Debug, Default)]
+pub struct DriverParameters<'m> {
+    preferred_driver: Option<&'m str>,
+    default_driver: Option<&'m str>,
+    options: BTreeMap<&'m str, BTreeMap<&'m str, &'m str>>,
+    overrides: Vec<(&'m str, &'m str)>,
+}
+
+impl<'m> DriverParameters<'m> {
+    /// Set the driver preferred by the document, used if none can be inferred from the output
+    /// path.
+    pub fn set_preferred_driver(&mut self, driver: &'m str) {
+        self.preferred_driver = Some(driver);
+    }
+
+    pub fn preferred_driver(&self) -> Option<&'m str> {
+        self.preferred_driver
+    }
+
+    /// Set the driver to fall back to when no other is specified.
+    pub fn set_default_driver(&mut self, driver: &'m str) {
+        self.default_driver = Some(driver);
+    }
+
+    pub fn default_driver(&self) -> Option<&'m str> {
+        self.default_driver
+    }
+
+    /// Set the options for a particular driver, as declared in the manifest.
+    pub fn set_options(&mut self, driver: &'m str, options: BTreeMap<&'m str, &'m str>) {
+        self.options.insert(driver, options);
+    }
+
+    /// Set options which apply to whichever driver is selected, taking precedence over those set
+    /// for the driver itself.
+    pub fn set_overrides(&mut self, overrides: Vec<(&'m str, &'m str)>) {
+        self.overrides = overrides;
+    }
+
+    /// Returns the options to pass to the given driver.
+    pub fn options_for(&self, driver: &str) -> BTreeMap<&'m str, &'m str> {
+        let mut ret = self.options.get(driver).cloned().unwrap_or_default();
+        ret.extend(self.overrides.iter().copied());
+        ret
+    }
+}
+
+/// Commands to run around a build. Each command is a program followed by its arguments.
+#[derive(Debug, Default)]
+pub struct HookParameters<'m> {
+    pre_build: Vec<Vec<&'m str>>,
+    post_build: Vec<Vec<&'m str>>,
+}
+
+impl<'m> HookParameters<'m> {
+    /// Set the commands to run before the document is parsed, in the order given.
+    pub fn set_pre_build(&mut self, pre_build: Vec<Vec<&'m str>>) {
+        self.pre_build = pre_build;
+    }
+
+    pub fn pre_build(&self) -> &[Vec<&'m str>] {
+        &self.pre_build
+    }
+
+    /// Set the commands to run once the output has been written, in the order given.
+    pub fn set_post_build(&mut self, post_build: Vec<Vec<&'m str>>) {
+        self.post_build = post_build;
+    }
+
+    pub fn post_build(&self) -> &[Vec<&'m str>] {
+        &self.post_build
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticParameters<'m> {
+    severities: HashMap<&'m str, Severity>,
+    warnings_as_errors: bool,
+}
+
+impl<'m> DiagnosticParameters<'m> {
+    /// Override the severity of all messages with the given ID. Message IDs have the form `Eddd`;
+    /// lint IDs may also be used.
+    pub fn set_severity(&mut self, id: &'m str, severity: Severity) {
+        self.severities.insert(id, severity);
+    }
+
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        self.severities.get(id).copied()
+    }
+
+    /// Treat warnings as errors when deciding whether an action succeeded.
+    pub fn set_warnings_as_errors(&mut self, warnings_as_errors: bool) {
+        self.warnings_as_errors = warnings_as_errors;
+    }
+
+    pub fn warnings_as_errors(&self) -> bool {
+        self.warnings_as_errors
+    }
+
+    /// Returns whether none of the given logs would be errors once severity overrides are
+    /// applied. Warnings count as errors if warnings are treated as errors.
+    pub fn successful(&self, logs: &[Log<'_>]) -> bool {
+        logs.iter()
+            .all(|log| match log.id().and_then(|id| self.severity(id)) {
+                None => log.successful(self.warnings_as_errors),
+                Some(Severity::Allow) => true,
+                Some(Severity::Warn) => !self.warnings_as_errors,
+                Some(Severity::Deny) => false,
+            })
+    }
+
+    /// Apply severity overrides to the given logs, removing any which are allowed.
+    pub fn apply<'i>(&self, logs: Vec<Log<'i>>) -> Vec<Log<'i>> {
+        logs.into_iter()
+            .filter_map(|log| match log.id().and_then(|id| self.severity(id)) {
+                None => Some(log),
+                Some(Severity::Allow) => None,
+                Some(Severity::Warn) => Some(log.with_msg_type(AnnotationType::Warning)),
+                Some(Severity::Deny) => Some(log.with_msg_type(AnnotationType::Error)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// Suppress the message entirely
+    Allow,
+
+    /// Report the message as a warning
+    Warn,
+
+    /// Report the message as an error
+    Deny,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ResourceLimit<T: Bounded + Clone + Integer> {
     Unlimited,
@@ -304,4 +643,104 @@ mod test {
         let result = ctx.alloc_file(content.clone());
         assert_eq!(result, content);
     }
+
+    #[test]
+    fn approx_memory_usage() {
+        let ctx = Context::with_arena_chunk_size(1);
+        let initial = ctx.approx_memory_usage();
+        assert!(initial > 0);
+
+        ctx.alloc_file("a".repeat(1000));
+        ctx.alloc_file("b".repeat(1000));
+        assert!(ctx.approx_memory_usage() >= initial + 2000);
+    }
+
+    #[test]
+    fn severity_overrides() {
+        let mut ctx = Context::test_new();
+        ctx.diagnostic_params_mut()
+            .set_severity("allowed", Severity::Allow);
+        ctx.diagnostic_params_mut()
+            .set_severity("warned", Severity::Warn);
+        ctx.diagnostic_params_mut()
+            .set_severity("denied", Severity::Deny);
+
+        let logs = ctx.diagnostic_params().apply(vec![
+            Log::warn("allowed warning").with_id("allowed"),
+            Log::error("warned error").with_id("warned"),
+            Log::warn("denied warning").with_id("denied"),
+            Log::warn("untouched warning").with_id("untouched"),
+            Log::error("untouched error"),
+        ]);
+
+        assert_eq!(
+            vec![
+                ("warned error", AnnotationType::Warning),
+                ("denied warning", AnnotationType::Error),
+                ("untouched warning", AnnotationType::Warning),
+                ("untouched error", AnnotationType::Error),
+            ],
+            logs.iter()
+                .map(|log| (log.msg(), log.msg_type()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn successful() {
+        let mut ctx = Context::test_new();
+        ctx.diagnostic_params_mut()
+            .set_severity("allowed", Severity::Allow);
+        ctx.diagnostic_params_mut()
+            .set_severity("warned", Severity::Warn);
+        ctx.diagnostic_params_mut()
+            .set_severity("denied", Severity::Deny);
+
+        for warnings_as_errors in [false, true] {
+            ctx.diagnostic_params_mut()
+                .set_warnings_as_errors(warnings_as_errors);
+            let params = ctx.diagnostic_params();
+
+            assert!(params.successful(&[]));
+            assert!(params.successful(&[Log::error("foo").with_id("allowed")]));
+            assert!(!params.successful(&[Log::warn("foo").with_id("denied")]));
+            assert!(!params.successful(&[Log::error("foo")]));
+            assert_eq!(
+                !warnings_as_errors,
+                params.successful(&[Log::error("foo").with_id("warned")])
+            );
+            assert_eq!(
+                !warnings_as_errors,
+                params.successful(&[Log::warn("foo").with_id("untouched")])
+            );
+        }
+    }
+
+    #[test]
+    fn driver_options() {
+        let mut ctx = Context::test_new();
+        assert!(ctx.driver_params().options_for("html").is_empty());
+
+        ctx.driver_params_mut().set_options(
+            "html",
+            [("theme", "light"), ("toc", "true")].into_iter().collect(),
+        );
+        ctx.driver_params_mut()
+            .set_options("pdf", [("compress", "true")].into_iter().collect());
+        ctx.driver_params_mut()
+            .set_overrides(vec![("theme", "dark"), ("lang", "en")]);
+
+        assert_eq!(
+            BTreeMap::from([("theme", "dark"), ("toc", "true"), ("lang", "en")]),
+            ctx.driver_params().options_for("html")
+        );
+        assert_eq!(
+            BTreeMap::from([("compress", "true"), ("theme", "dark"), ("lang", "en")]),
+            ctx.driver_params().options_for("pdf")
+        );
+        assert_eq!(
+            BTreeMap::from([("theme", "dark"), ("lang", "en")]),
+            ctx.driver_params().options_for("epub")
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use derive_new::new;
 
@@ -8,7 +8,7 @@ pub struct Module<'m> {
     source: &'m str,
     rename_as: Option<&'m str>,
     version: ModuleVersion<'m>,
-    args: HashMap<&'m str, &'m str>,
+    args: BTreeMap<&'m str, &'m str>,
 }
 
 impl<'m> Module<'m> {
@@ -35,11 +35,11 @@ impl<'m> Module<'m> {
         self.version
     }
 
-    pub fn args(&self) -> &HashMap<&'m str, &'m str> {
+    pub fn args(&self) -> &BTreeMap<&'m str, &'m str> {
         &self.args
     }
 
-    pub fn args_mut(&mut self) -> &mut HashMap<&'m str, &'m str> {
+    pub fn args_mut(&mut self) -> &mut BTreeMap<&'m str, &'m str> {
         &mut self.args
     }
 }
@@ -61,7 +61,7 @@ mod test {
         let source = "github.com/TheSignPainter98/some-repo";
         let rename = "some-new-name";
         let version = ModuleVersion::Tag("some-tag");
-        let args: HashMap<_, _> = [("foo", "bar"), ("baz", "qux")].into_iter().collect();
+        let args: BTreeMap<_, _> = [("foo", "bar"), ("baz", "qux")].into_iter().collect();
 
         let dep = Module::new(name, source, Some(rename), version, args.clone());
         assert_eq!(name, dep.name());
@@ -75,7 +75,7 @@ mod test {
     fn rename_as() {
         assert_eq!(
             None,
-            Module::new("foo", ".", None, ModuleVersion::Tag("bar"), HashMap::new()).rename_as()
+            Module::new("foo", ".", None, ModuleVersion::Tag("bar"), BTreeMap::new()).rename_as()
         );
 
         let expected = "new-name";
@@ -86,7 +86,7 @@ mod test {
                 ".",
                 Some(expected),
                 ModuleVersion::Tag("bar"),
-                HashMap::new()
+                BTreeMap::new()
             )
             .rename_as()
             .unwrap()
@@ -98,19 +98,19 @@ mod test {
         let tag = ModuleVersion::Tag("bar");
         assert_eq!(
             tag,
-            Module::new("foo", ".", None, tag, HashMap::new()).version()
+            Module::new("foo", ".", None, tag, BTreeMap::new()).version()
         );
 
         let branch = ModuleVersion::Branch("bar");
         assert_eq!(
             branch,
-            Module::new("foo", ".", None, branch, HashMap::new()).version()
+            Module::new("foo", ".", None, branch, BTreeMap::new()).version()
         );
 
         let hash = ModuleVersion::Hash("bar");
         assert_eq!(
             hash,
-            Module::new("foo", ".", None, hash, HashMap::new()).version()
+            Module::new("foo", ".", None, hash, BTreeMap::new()).version()
         );
     }
 }
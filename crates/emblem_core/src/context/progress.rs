@@ -0,0 +1,124 @@
+/// A stage of a build which reports its progress.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Phase {
+    /// Reading and parsing source files
+    Parse,
+
+    /// Iterating over the document until it settles
+    Typeset,
+}
+
+/// Receives progress events as a build runs, for example to draw a progress bar. All methods do
+/// nothing by default.
+pub trait ProgressSink {
+    /// Called when a phase begins.
+    fn phase_start(&self, _phase: Phase) {}
+
+    /// Called when a phase ends.
+    fn phase_end(&self, _phase: Phase) {}
+
+    /// Called as a phase progresses, where `done` units of work are complete. While parsing, a
+    /// unit is a source file; while typesetting, it is a pass over the document. The `total` is
+    /// given only when the number of units is known in advance, and is never a limit which may
+    /// not be reached. As files may import others and extensions may request further passes,
+    /// neither phase currently knows its total, so it is `None`.
+    fn progress(&self, _phase: Phase, _done: u32, _total: Option<u32>) {}
+}
+
+/// A set of progress sinks, each of which receives every event.
+#[derive(Default)]
+pub(crate) struct ProgressSinks<'m> {
+    sinks: Vec<Box<dyn ProgressSink + 'm>>,
+}
+
+impl<'m> ProgressSinks<'m> {
+    pub(crate) fn push(&mut self, sink: Box<dyn ProgressSink + 'm>) {
+        self.sinks.push(sink);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl ProgressSink for ProgressSinks<'_> {
+    fn phase_start(&self, phase: Phase) {
+        for sink in &self.sinks {
+            sink.phase_start(phase);
+        }
+    }
+
+    fn phase_end(&self, phase: Phase) {
+        for sink in &self.sinks {
+            sink.phase_end(phase);
+        }
+    }
+
+    fn progress(&self, phase: Phase, done: u32, total: Option<u32>) {
+        for sink in &self.sinks {
+            sink.progress(phase, done, total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder {
+        name: &'static str,
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ProgressSink for Recorder {
+        fn phase_start(&self, phase: Phase) {
+            self.events
+                .borrow_mut()
+                .push(format!("{}: start {phase:?}", self.name));
+        }
+
+        fn phase_end(&self, phase: Phase) {
+            self.events
+                .borrow_mut()
+                .push(format!("{}: end {phase:?}", self.name));
+        }
+
+        fn progress(&self, phase: Phase, done: u32, total: Option<u32>) {
+            self.events
+                .borrow_mut()
+                .push(format!("{}: {phase:?} {done}/{total:?}", self.name));
+        }
+    }
+
+    #[test]
+    fn all_sinks_notified() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut sinks = ProgressSinks::default();
+        assert!(sinks.is_empty());
+        for name in ["a", "b"] {
+            sinks.push(Box::new(Recorder {
+                name,
+                events: events.clone(),
+            }));
+        }
+        assert!(!sinks.is_empty());
+
+        sinks.phase_start(Phase::Parse);
+        sinks.progress(Phase::Parse, 1, Some(2));
+        sinks.phase_end(Phase::Parse);
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                "a: start Parse",
+                "b: start Parse",
+                "a: Parse 1/Some(2)",
+                "b: Parse 1/Some(2)",
+                "a: end Parse",
+                "b: end Parse",
+            ]
+        );
+    }
+}
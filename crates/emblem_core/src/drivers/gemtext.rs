@@ -0,0 +1,286 @@
+use crate::ast::{Dash, Glue};
+use crate::drivers::{Driver, DriverOption, DriverOptions, OptionKind};
+use crate::parser::Location;
+use crate::{Doc, DocElem};
+use std::io::{self, Write};
+use std::ptr;
+
+/// Writes documents as gemtext, the markup language of the Gemini protocol. Gemtext is
+/// line-oriented and has no inline styling, so each paragraph becomes a single line of plain text.
+pub(crate) struct Gemtext;
+
+impl Driver for Gemtext {
+    fn name(&self) -> &'static str {
+        "gemtext"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["gmi", "gemini"]
+    }
+
+    fn options(&self) -> &'static [DriverOption] {
+        &[DriverOption::new("compact", OptionKind::Bool)]
+    }
+
+    fn write(
+        &self,
+        doc: &Doc<'_>,
+        options: &DriverOptions<'_>,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        Writer {
+            out,
+            first: true,
+            compact: options.bool("compact").unwrap_or(false),
+        }
+        .block(doc)
+    }
+}
+
+struct Writer<'w> {
+    out: &'w mut dyn Write,
+    first: bool,
+
+    /// Whether to omit the empty lines between blocks.
+    compact: bool,
+}
+
+impl Writer<'_> {
+    fn block(&mut self, elem: &DocElem<'_>) -> io::Result<()> {
+        match elem {
+            DocElem::Content(elems) => {
+                for elem in elems {
+                    self.block(elem)?;
+                }
+                Ok(())
+            }
+            DocElem::Command { name, args, .. } => match name.as_str() {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    // Gemtext only has three levels of heading.
+                    let level = name[1..].parse::<usize>().unwrap_or(1).min(3);
+                    self.line(&format!("{} {}", "#".repeat(level), inline(args)))
+                }
+                "tt" => {
+                    self.start_block()?;
+                    writeln!(self.out, "```\n{}\n```", inline(args))
+                }
+                _ => self.text(&inline(args)),
+            },
+            DocElem::Word { .. } | DocElem::Dash { .. } | DocElem::Glue { .. } => {
+                self.text(&inline([elem]))
+            }
+        }
+    }
+
+    /// Write a line of text, taking care that it is not mistaken for another type of line.
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        const LINE_TYPE_PREFIXES: &[&str] = &["#", "=>", "* ", ">", "```"];
+        if LINE_TYPE_PREFIXES
+            .iter()
+            .any(|prefix| text.starts_with(prefix))
+        {
+            self.line(&format!(" {text}"))
+        } else {
+            self.line(text)
+        }
+    }
+
+    fn line(&mut self, line: &str) -> io::Result<()> {
+        self.start_block()?;
+        writeln!(self.out, "{line}")
+    }
+
+    /// Separate each block from the last with an empty line.
+    fn start_block(&mut self) -> io::Result<()> {
+        if !self.first && !self.compact {
+            writeln!(self.out)?;
+        }
+        self.first = false;
+        Ok(())
+    }
+}
+
+/// Render content as a single line of text.
+fn inline<'a, 'em: 'a>(elems: impl IntoIterator<Item = &'a DocElem<'em>>) -> String {
+    let mut line = Line::default();
+    for elem in elems {
+        line.elem(elem);
+    }
+    line.text
+}
+
+/// A line of text under construction. Pieces of text are separated by a space only where the
+/// source separated them with whitespace, so that, for example, punctuation after a styled word
+/// stays attached to it.
+#[derive(Default)]
+struct Line<'em> {
+    text: String,
+
+    /// The source which the last piece of text came from, and the index at which it ended.
+    last_end: Option<(&'em str, usize)>,
+
+    nbsp: bool,
+}
+
+impl<'em> Line<'em> {
+    fn elem(&mut self, elem: &DocElem<'em>) {
+        match elem {
+            DocElem::Word { word, loc } => self.push(word, loc),
+            DocElem::Dash { dash, loc } => self.push(
+                match dash {
+                    Dash::Hyphen => "-",
+                    Dash::En => "\u{2013}",
+                    Dash::Em => "\u{2014}",
+                },
+                loc,
+            ),
+            DocElem::Glue { glue, .. } => self.nbsp = *glue == Glue::Nbsp,
+            DocElem::Command { args, .. } => {
+                for arg in args {
+                    self.elem(arg);
+                }
+            }
+            DocElem::Content(elems) => {
+                for elem in elems {
+                    self.elem(elem);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, s: &str, loc: &Location<'em>) {
+        let src = loc.src();
+        let start = loc.start().index;
+        if !self.text.is_empty() {
+            if self.nbsp {
+                self.text.push('\u{a0}');
+            } else if self.spaced(src, start) {
+                self.text.push(' ');
+            }
+        }
+        self.text.push_str(s);
+        self.last_end = Some((src, loc.end().index));
+        self.nbsp = false;
+    }
+
+    /// Returns whether the source separated the last piece of text from one starting at `start`.
+    fn spaced(&self, src: &str, start: usize) -> bool {
+        match self.last_end {
+            Some((last_src, end)) if ptr::eq(last_src, src) && end <= start => {
+                src[end..start].contains(char::is_whitespace)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser, Context};
+
+    fn render(src: &str) -> String {
+        render_with(src, &[])
+    }
+
+    fn render_with(src: &str, options: &[(&str, &str)]) -> String {
+        let ctx = Context::test_new();
+        let doc: Doc = parser::parse(
+            ctx.alloc_file_name("main.em"),
+            ctx.alloc_file(textwrap::dedent(src)),
+        )
+        .unwrap()
+        .into();
+        let options = DriverOptions::parse(&Gemtext, options.iter().copied().collect()).unwrap();
+
+        let mut out = Vec::new();
+        Gemtext.write(&doc, &options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn paragraphs() {
+        assert_eq!("", render(""));
+        assert_eq!("hello, world\n", render("hello, world"));
+        assert_eq!(
+            "hello, world\n\nhow are you?\n",
+            render("hello,\nworld\n\nhow are you?")
+        );
+    }
+
+    #[test]
+    fn headings() {
+        assert_eq!(
+            "# Title\n\n## Section\n\n### Deep\n\n### Deeper\n\ntext\n",
+            render(
+                r"
+                    # Title
+
+                    ## Section
+
+                    ### Deep
+
+                    #### Deeper
+
+                    text
+                "
+            )
+        );
+    }
+
+    #[test]
+    fn preformatted() {
+        assert_eq!("intro\n\n```\nlet x\n```\n", render("intro\n\n`let x`"));
+    }
+
+    #[test]
+    fn inline_styles() {
+        assert_eq!(
+            "some emphasised, bold and custom text\n",
+            render("some _emphasised_, **bold** and .custom{custom} text")
+        );
+    }
+
+    #[test]
+    fn dashes_and_glue() {
+        assert_eq!(
+            "well-known, 1\u{2013}5, yes\u{2014}no\n",
+            render("well-known, 1--5, yes---no")
+        );
+        assert_eq!("foo \u{2014} bar\n", render("foo --- bar"));
+        assert_eq!("ab c\u{a0}d\n", render("a~b c~~d"));
+    }
+
+    #[test]
+    fn verbatim_whitespace() {
+        assert_eq!("  foo\tbar  \n", render("!  foo\tbar  !"));
+        assert_eq!("a b  c d\n", render("a !b  c! d"));
+        assert_eq!("a\t\tb\n", render("!a\t\tb!"));
+        assert_eq!(
+            "intro\n\n```\n\tlet  x = 1 \n```\n",
+            render("intro\n\n`!\tlet  x = 1 !`")
+        );
+    }
+
+    #[test]
+    fn compact() {
+        let src = "# Title\n\nhello\n\n`let x`";
+        assert_eq!(
+            "# Title\n\nhello\n\n```\nlet x\n```\n",
+            render_with(src, &[("compact", "false")])
+        );
+        assert_eq!(
+            "# Title\nhello\n```\nlet x\n```\n",
+            render_with(src, &[("compact", "true")])
+        );
+    }
+
+    #[test]
+    fn line_types_escaped() {
+        assert_eq!(" > not a quote\n", render("> not a quote"));
+    }
+}
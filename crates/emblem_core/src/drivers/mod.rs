@@ -0,0 +1,297 @@
+mod gemtext;
+mod options;
+mod sink;
+
+pub use options::{DriverOption, DriverOptions, OptionKind, OptionValue};
+pub use sink::{PendingFile, Sink};
+
+use crate::{
+    args::ArgPath,
+    context::DriverParameters,
+    log::{
+        messages::{DriverNotInferred, NoSuchDriver},
+        Message,
+    },
+    Doc, Log,
+};
+use std::io::{self, Write};
+
+/// An output format in which a typeset document can be written.
+pub trait Driver {
+    /// The name used to select this driver.
+    fn name(&self) -> &'static str;
+
+    /// File extensions, without a leading dot, which imply this driver.
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The options this driver accepts. Any other option given to it is rejected.
+    fn options(&self) -> &'static [DriverOption] {
+        &[]
+    }
+
+    /// Write the given document to the output. Output should be written as it is produced, so
+    /// that the whole of a large document need never be held in memory.
+    fn write(
+        &self,
+        doc: &Doc<'_>,
+        options: &DriverOptions<'_>,
+        out: &mut dyn Write,
+    ) -> io::Result<()>;
+}
+
+pub type Drivers = Vec<Box<dyn Driver>>;
+
+/// The extension of emblem source files.
+const SOURCE_EXTENSION: &str = "em";
+
+pub fn drivers() -> Drivers {
+    macro_rules! drivers {
+        ($($driver:expr),* $(,)?) => {
+            vec![
+                $(Box::new($driver),)*
+            ]
+        }
+    }
+
+    drivers![gemtext::Gemtext]
+}
+
+/// Choose the driver to use. An explicitly-requested driver is used if given, otherwise one is
+/// inferred from the extension of the output path, then the driver preferred by the document,
+/// then the configured default. If none of these is set, only one driver is available and the
+/// output path has no extension of its own, that driver is used.
+pub fn infer<'d, 'i>(
+    drivers: &'d [Box<dyn Driver>],
+    requested: Option<&str>,
+    output_stem: &ArgPath,
+    params: &DriverParameters<'_>,
+) -> Result<&'d dyn Driver, Log<'i>> {
+    let by_name = |name: &str| {
+        drivers
+            .iter()
+            .find(|driver| driver.name() == name)
+            .map(|driver| driver.as_ref())
+            .ok_or_else(|| NoSuchDriver::new(name.into(), names(drivers)).log())
+    };
+
+    if let Some(requested) = requested {
+        return by_name(requested);
+    }
+
+    // An output stem taken from the input file keeps the source extension, which names no output
+    // format.
+    let extension = match output_stem {
+        ArgPath::Path(path) => path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|&ext| ext != SOURCE_EXTENSION),
+        ArgPath::Stdio => None,
+    };
+    if let Some(extension) = extension {
+        if let Some(driver) = drivers
+            .iter()
+            .find(|driver| driver.extensions().contains(&extension))
+        {
+            return Ok(driver.as_ref());
+        }
+    }
+
+    match (
+        params.preferred_driver().or(params.default_driver()),
+        extension,
+        drivers,
+    ) {
+        (Some(name), _, _) => by_name(name),
+        (None, None, [only]) => Ok(only.as_ref()),
+        (None, _, _) => Err(DriverNotInferred::new(names(drivers)).log()),
+    }
+}
+
+/// Find where a driver should write its output. Unless the output stem already has one of the
+/// driver's extensions, its extension is replaced with the driver's first.
+pub(crate) fn output_path(output_stem: &ArgPath, driver: &dyn Driver) -> ArgPath {
+    let ArgPath::Path(stem) = output_stem else {
+        return ArgPath::Stdio;
+    };
+
+    let extensions = driver.extensions();
+    match stem.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if extensions.contains(&ext) => ArgPath::Path(stem.clone()),
+        _ => ArgPath::Path(stem.with_extension(extensions.first().copied().unwrap_or(""))),
+    }
+}
+
+fn names(drivers: &[Box<dyn Driver>]) -> Vec<&'static str> {
+    drivers.iter().map(|driver| driver.name()).collect()
+}
+
+/// Describe the given set of available drivers.
+pub(crate) fn describe(names: &[&str]) -> String {
+    if names.is_empty() {
+        return "no output drivers are available".into();
+    }
+    format!("available drivers: {}", names.join(", "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+
+    struct TestDriver {
+        name: &'static str,
+        extensions: &'static [&'static str],
+    }
+
+    impl Driver for TestDriver {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn extensions(&self) -> &'static [&'static str] {
+            self.extensions
+        }
+
+        fn write(
+            &self,
+            _doc: &Doc<'_>,
+            _options: &DriverOptions<'_>,
+            out: &mut dyn Write,
+        ) -> io::Result<()> {
+            write!(out, "{}", self.name)
+        }
+    }
+
+    fn test_drivers() -> Drivers {
+        vec![
+            Box::new(TestDriver {
+                name: "html",
+                extensions: &["html", "htm"],
+            }),
+            Box::new(TestDriver {
+                name: "text",
+                extensions: &["txt"],
+            }),
+        ]
+    }
+
+    fn infer_name(
+        requested: Option<&str>,
+        output_stem: &str,
+        ctx: &Context,
+    ) -> Result<&'static str, String> {
+        let drivers = test_drivers();
+        let output_stem = match output_stem {
+            "-" => ArgPath::Stdio,
+            path => ArgPath::Path(path.into()),
+        };
+        infer(&drivers, requested, &output_stem, ctx.driver_params())
+            .map(|driver| driver.name())
+            .map_err(|log| format!("{}: {}", log.msg(), log.note().as_deref().unwrap_or("")))
+    }
+
+    #[test]
+    fn unique_names() {
+        let drivers = drivers();
+        let mut names = names(&drivers);
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), drivers.len());
+    }
+
+    #[test]
+    fn requested() {
+        let ctx = Context::test_new();
+        assert_eq!(Ok("text"), infer_name(Some("text"), "out.html", &ctx));
+        assert_eq!(
+            Err("no such output driver ‘pdf’: available drivers: html, text".into()),
+            infer_name(Some("pdf"), "out.html", &ctx)
+        );
+    }
+
+    #[test]
+    fn extension() {
+        let ctx = Context::test_new();
+        assert_eq!(Ok("html"), infer_name(None, "out.html", &ctx));
+        assert_eq!(Ok("html"), infer_name(None, "out.htm", &ctx));
+        assert_eq!(Ok("text"), infer_name(None, "dir.d/out.txt", &ctx));
+    }
+
+    #[test]
+    fn preferred_then_default() {
+        let mut ctx = Context::test_new();
+        assert_eq!(
+            Err("could not infer output format: available drivers: html, text".into()),
+            infer_name(None, "main.em", &ctx)
+        );
+        assert_eq!(
+            Err("could not infer output format: available drivers: html, text".into()),
+            infer_name(None, "out.pdf", &ctx)
+        );
+
+        ctx.driver_params_mut().set_default_driver("text");
+        assert_eq!(Ok("text"), infer_name(None, "main.em", &ctx));
+        assert_eq!(Ok("text"), infer_name(None, "-", &ctx));
+        assert_eq!(Ok("html"), infer_name(None, "main.html", &ctx));
+
+        ctx.driver_params_mut().set_preferred_driver("html");
+        assert_eq!(Ok("html"), infer_name(None, "main.em", &ctx));
+        assert_eq!(Ok("text"), infer_name(None, "main.txt", &ctx));
+    }
+
+    #[test]
+    fn only_driver() {
+        let mut drivers = test_drivers();
+        drivers.truncate(1);
+
+        let mut ctx = Context::test_new();
+        let infer_name = |output_stem: ArgPath, ctx: &Context| {
+            infer(&drivers, None, &output_stem, ctx.driver_params())
+                .map(|driver| driver.name())
+                .map_err(|log| log.msg().to_owned())
+        };
+        assert_eq!(
+            Ok("html"),
+            infer_name(ArgPath::Path("main.em".into()), &ctx)
+        );
+        assert_eq!(Ok("html"), infer_name(ArgPath::Path("main".into()), &ctx));
+        assert_eq!(Ok("html"), infer_name(ArgPath::Stdio, &ctx));
+        assert_eq!(
+            Err("could not infer output format".into()),
+            infer_name(ArgPath::Path("out.pdf".into()), &ctx)
+        );
+
+        ctx.driver_params_mut().set_default_driver("text");
+        assert_eq!(
+            Err("no such output driver ‘text’".into()),
+            infer_name(ArgPath::Path("main.em".into()), &ctx)
+        );
+    }
+
+    #[test]
+    fn output_path() {
+        let drivers = test_drivers();
+        let html = drivers[0].as_ref();
+        let output_path = |stem: &str| match super::output_path(&ArgPath::Path(stem.into()), html) {
+            ArgPath::Path(path) => path.to_str().unwrap().to_owned(),
+            ArgPath::Stdio => "-".into(),
+        };
+
+        assert_eq!("main.html", output_path("main.em"));
+        assert_eq!("main.html", output_path("main"));
+        assert_eq!("main.htm", output_path("main.htm"));
+        assert_eq!("dir.d/main.html", output_path("dir.d/main.em"));
+        assert!(matches!(
+            super::output_path(&ArgPath::Stdio, html),
+            ArgPath::Stdio
+        ));
+    }
+
+    #[test]
+    fn describe() {
+        assert_eq!("no output drivers are available", super::describe(&[]));
+        assert_eq!("available drivers: a, b", super::describe(&["a", "b"]));
+    }
+}
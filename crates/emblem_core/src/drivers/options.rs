@@ -0,0 +1,198 @@
+use crate::drivers::Driver;
+use crate::log::messages::{InvalidDriverOption, Message};
+use crate::Log;
+use std::collections::BTreeMap;
+
+/// An option accepted by a driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DriverOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+}
+
+impl DriverOption {
+    pub const fn new(name: &'static str, kind: OptionKind) -> Self {
+        Self { name, kind }
+    }
+}
+
+/// The values an option may take.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptionKind {
+    /// Either `true` or `false`.
+    Bool,
+
+    /// One of the given words.
+    Choice(&'static [&'static str]),
+
+    /// Any text.
+    Text,
+}
+
+impl OptionKind {
+    fn parse<'m>(&self, value: &'m str) -> Option<OptionValue<'m>> {
+        match self {
+            Self::Bool => match value {
+                "true" => Some(OptionValue::Bool(true)),
+                "false" => Some(OptionValue::Bool(false)),
+                _ => None,
+            },
+            Self::Choice(choices) => choices.contains(&value).then_some(OptionValue::Text(value)),
+            Self::Text => Some(OptionValue::Text(value)),
+        }
+    }
+
+    /// Describe the values this kind of option accepts.
+    fn expected(&self) -> String {
+        match self {
+            Self::Bool => "true or false".into(),
+            Self::Choice(choices) => format!("one of: {}", choices.join(", ")),
+            Self::Text => "any text".into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptionValue<'m> {
+    Bool(bool),
+    Text(&'m str),
+}
+
+/// Options given to a driver, each checked against the driver's declaration.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct DriverOptions<'m> {
+    values: BTreeMap<&'static str, OptionValue<'m>>,
+}
+
+impl<'m> DriverOptions<'m> {
+    /// Check the given options against those declared by the driver. All unknown options and
+    /// invalid values are reported.
+    pub(crate) fn parse<'i>(
+        driver: &dyn Driver,
+        raw: BTreeMap<&'m str, &'m str>,
+    ) -> Result<Self, Vec<Log<'i>>> {
+        let declared = driver.options();
+
+        let mut values = BTreeMap::new();
+        let mut problems = Vec::new();
+        for (name, value) in raw {
+            let Some(option) = declared.iter().find(|option| option.name == name) else {
+                let known = declared.iter().map(|option| option.name).collect();
+                problems
+                    .push(InvalidDriverOption::new(driver.name(), name.into(), None, known).log());
+                continue;
+            };
+
+            match option.kind.parse(value) {
+                Some(value) => {
+                    values.insert(option.name, value);
+                }
+                None => problems.push(
+                    InvalidDriverOption::new(
+                        driver.name(),
+                        name.into(),
+                        Some(option.kind.expected()),
+                        vec![],
+                    )
+                    .log(),
+                ),
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(Self { values })
+    }
+
+    /// Returns the value of a `Bool` option, if it was given.
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.values.get(name)? {
+            OptionValue::Bool(b) => Some(*b),
+            OptionValue::Text(_) => None,
+        }
+    }
+
+    /// Returns the value of a `Choice` or `Text` option, if it was given.
+    pub fn text(&self, name: &str) -> Option<&'m str> {
+        match self.values.get(name)? {
+            OptionValue::Text(text) => Some(*text),
+            OptionValue::Bool(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Doc;
+    use std::io::{self, Write};
+
+    struct OptionsDriver;
+
+    impl Driver for OptionsDriver {
+        fn name(&self) -> &'static str {
+            "opts"
+        }
+
+        fn options(&self) -> &'static [DriverOption] {
+            &[
+                DriverOption::new("toc", OptionKind::Bool),
+                DriverOption::new("theme", OptionKind::Choice(&["light", "dark"])),
+                DriverOption::new("title", OptionKind::Text),
+            ]
+        }
+
+        fn write(
+            &self,
+            _doc: &Doc<'_>,
+            _options: &DriverOptions<'_>,
+            _out: &mut dyn Write,
+        ) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn parse(raw: &[(&'static str, &'static str)]) -> Result<DriverOptions<'static>, Vec<String>> {
+        DriverOptions::parse(&OptionsDriver, raw.iter().copied().collect()).map_err(|problems| {
+            problems
+                .iter()
+                .map(|log| format!("{}: {}", log.msg(), log.note().as_deref().unwrap_or("")))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn valid() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(None, options.bool("toc"));
+        assert_eq!(None, options.text("theme"));
+
+        let options = parse(&[("toc", "true"), ("theme", "dark"), ("title", "")]).unwrap();
+        assert_eq!(Some(true), options.bool("toc"));
+        assert_eq!(Some("dark"), options.text("theme"));
+        assert_eq!(Some(""), options.text("title"));
+        assert_eq!(None, options.text("toc"));
+        assert_eq!(None, options.bool("theme"));
+
+        assert_eq!(Some(false), parse(&[("toc", "false")]).unwrap().bool("toc"));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Err(vec![
+                "unknown option ‘compress’ for driver ‘opts’: known options: toc, theme, title"
+                    .to_owned()
+            ]),
+            parse(&[("compress", "true")])
+        );
+        assert_eq!(
+            Err(vec![
+                "invalid value for option ‘theme’: expected one of: light, dark".to_owned(),
+                "invalid value for option ‘toc’: expected true or false".to_owned(),
+            ]),
+            parse(&[("toc", "yes"), ("theme", "blue"), ("title", "ok")])
+        );
+    }
+}
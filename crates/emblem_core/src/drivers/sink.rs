@@ -0,0 +1,157 @@
+use crate::args::ArgPath;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// A buffered destination for driver output, which lets drivers write documents as they go rather
+/// than building them up in memory first. Output is only guaranteed to reach its destination once
+/// the sink is finished.
+pub enum Sink {
+    File(PendingFile),
+    Stdout(BufWriter<Stdout>),
+}
+
+impl Sink {
+    /// Open a sink which writes to the given path, or to stdout if the path is `-`.
+    pub fn create(path: &ArgPath) -> io::Result<Self> {
+        match path {
+            ArgPath::Stdio => Ok(Self::stdout()),
+            ArgPath::Path(path) => Self::file(path),
+        }
+    }
+
+    pub fn file(path: &Path) -> io::Result<Self> {
+        Ok(Self::File(PendingFile::create(path)?))
+    }
+
+    pub fn stdout() -> Self {
+        Self::Stdout(BufWriter::new(io::stdout()))
+    }
+
+    /// Flush all output to its destination. If this sink is dropped before it is finished, any
+    /// file it would have written is left untouched.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.finish(),
+            Self::Stdout(mut s) => s.flush(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::File(f) => f.out.write(buf),
+            Self::Stdout(s) => s.write(buf),
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.out.write_all(buf),
+            Self::Stdout(s) => s.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.out.flush(),
+            Self::Stdout(s) => s.flush(),
+        }
+    }
+}
+
+/// A file being written beside its destination, which replaces the destination only once it is
+/// finished. This way, a build which fails midway never truncates the output of the last good
+/// one.
+pub struct PendingFile {
+    out: BufWriter<File>,
+    tmp_path: PathBuf,
+    path: PathBuf,
+    finished: bool,
+}
+
+impl PendingFile {
+    fn create(path: &Path) -> io::Result<Self> {
+        let file_name = path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a file path", path.display()),
+            )
+        })?;
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(".{}.tmp", process::id()));
+        let tmp_path = path.with_file_name(tmp_name);
+
+        Ok(Self {
+            out: BufWriter::new(File::create(&tmp_path)?),
+            tmp_path,
+            path: path.to_owned(),
+            finished: false,
+        })
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.out.flush()?;
+        self.out.get_ref().sync_all()?;
+        fs::rename(&self.tmp_path, &self.path)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dir_contents(dir: &Path) -> io::Result<Vec<String>> {
+        let mut ret = fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        ret.sort();
+        Ok(ret)
+    }
+
+    #[test]
+    fn file() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("out.txt");
+
+        let mut sink = Sink::create(&ArgPath::Path(path.clone()))?;
+        write!(sink, "hello, ")?;
+        write!(sink, "world")?;
+        sink.finish()?;
+
+        assert_eq!("hello, world", fs::read_to_string(path)?);
+        assert_eq!(vec!["out.txt"], dir_contents(tmpdir.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn unfinished() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("out.txt");
+        fs::write(&path, "last good output")?;
+
+        let mut sink = Sink::create(&ArgPath::Path(path.clone()))?;
+        write!(sink, "partial")?;
+        sink.flush()?;
+        assert_eq!("last good output", fs::read_to_string(&path)?);
+        drop(sink);
+
+        assert_eq!("last good output", fs::read_to_string(&path)?);
+        assert_eq!(vec!["out.txt"], dir_contents(tmpdir.path())?);
+        Ok(())
+    }
+}
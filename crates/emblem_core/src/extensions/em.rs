@@ -1,12 +1,20 @@
+use crate::context::SandboxLevel;
+use crate::extensions::exec;
 use derive_new::new;
 use mlua::{MetaMethod, UserData};
 
 #[derive(new)]
-pub(crate) struct Em {}
+pub(crate) struct Em {
+    sandbox_level: SandboxLevel,
+}
 
 impl UserData for Em {
     fn add_fields<'lua, F: mlua::UserDataFields<'lua, Self>>(fields: &mut F) {
         fields.add_field_method_get("version", |lua, _| lua.create_userdata(Version::new()));
+        fields.add_field_method_get("exec", |lua, this| {
+            let sandbox_level = this.sandbox_level;
+            lua.create_function(move |lua, args| exec::exec(lua, sandbox_level, args))
+        });
     }
 }
 
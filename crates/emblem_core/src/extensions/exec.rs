@@ -0,0 +1,231 @@
+use crate::context::SandboxLevel;
+use mlua::{Error as MLuaError, Lua, Result as MLuaResult, Table};
+use std::{
+    ffi::OsStr,
+    io::{self, Read},
+    process::{Child, Command, ExitStatus, Output, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Options accepted by `em.exec`.
+struct ExecOptions {
+    /// How long the program may run before it is killed.
+    timeout: Option<Duration>,
+
+    /// Whether to raise an error if the program exits unsuccessfully.
+    check: bool,
+}
+
+impl ExecOptions {
+    fn from_table(opts: Option<Table<'_>>) -> MLuaResult<Self> {
+        let Some(opts) = opts else {
+            return Ok(Self {
+                timeout: None,
+                check: true,
+            });
+        };
+
+        let timeout = match opts.get::<_, Option<f64>>("timeout")? {
+            Some(secs) if secs.is_finite() && secs > 0.0 => Some(Duration::from_secs_f64(secs)),
+            Some(secs) => {
+                return Err(MLuaError::RuntimeError(format!(
+                    "em.exec timeout must be a positive number of seconds: got {secs}"
+                )))
+            }
+            None => None,
+        };
+        let check = opts.get::<_, Option<bool>>("check")?.unwrap_or(true);
+        Ok(Self { timeout, check })
+    }
+}
+
+/// Run an external program on behalf of an extension, returning a table of its exit status and
+/// output. Programs may only be run when the sandbox is unrestricted.
+pub(crate) fn exec<'lua>(
+    lua: &'lua Lua,
+    sandbox_level: SandboxLevel,
+    (args, opts): (Vec<String>, Option<Table<'lua>>),
+) -> MLuaResult<Table<'lua>> {
+    if sandbox_level > SandboxLevel::Unrestricted {
+        return Err(MLuaError::SafetyError(
+            "em.exec is unavailable to the sandbox, try --sandbox unrestricted".into(),
+        ));
+    }
+
+    let opts = ExecOptions::from_table(opts)?;
+    let Some((program, args)) = args.split_first() else {
+        return Err(MLuaError::RuntimeError(
+            "em.exec requires a program to run".into(),
+        ));
+    };
+
+    let output = run(program, args, opts.timeout)
+        .map_err(|e| MLuaError::RuntimeError(format!("failed to run {program}: {e}")))?;
+    if opts.check && !output.status.success() {
+        return Err(MLuaError::RuntimeError(format!(
+            "{program} failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+
+    let ret = lua.create_table_with_capacity(0, 3)?;
+    ret.set("status", output.status.code())?;
+    ret.set("stdout", lua.create_string(&output.stdout)?)?;
+    ret.set("stderr", lua.create_string(&output.stderr)?)?;
+    Ok(ret)
+}
+
+/// Run a program to completion, capturing its output. If a timeout is given and the program is
+/// still running once it elapses, the program is killed.
+pub(crate) fn run(
+    program: &str,
+    args: &[impl AsRef<OsStr>],
+    timeout: Option<Duration>,
+) -> io::Result<Output> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Output is read as it is produced so that the program cannot block on a full pipe.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let status = wait(&mut child, timeout)?;
+
+    Ok(Output {
+        status,
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buf)?;
+        }
+        Ok(buf)
+    })
+}
+
+fn join(handle: JoinHandle<io::Result<Vec<u8>>>) -> io::Result<Vec<u8>> {
+    handle
+        .join()
+        .expect("internal error: output reader panicked")
+}
+
+fn wait(child: &mut Child, timeout: Option<Duration>) -> io::Result<ExitStatus> {
+    let Some(timeout) = timeout else {
+        return child.wait();
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("timed out after {}s", timeout.as_secs_f64()),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use crate::{context::SandboxLevel, Context};
+    use mlua::{Integer, Table};
+
+    fn with_lua(sandbox_level: SandboxLevel, f: impl FnOnce(&mlua::Lua)) {
+        let mut ctx = Context::test_new();
+        ctx.lua_params_mut().set_sandbox_level(sandbox_level);
+        let ext_state = ctx.extension_state().unwrap();
+        f(ext_state.lua());
+    }
+
+    #[test]
+    fn sandboxed() {
+        for level in [SandboxLevel::Standard, SandboxLevel::Strict] {
+            with_lua(level, |lua| {
+                let err = lua.load(r#"em.exec{"true"}"#).exec().unwrap_err();
+                assert!(
+                    err.to_string().contains("unavailable to the sandbox"),
+                    "{err}"
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn output() {
+        with_lua(SandboxLevel::Unrestricted, |lua| {
+            let result: Table = lua
+                .load(r#"return em.exec{"sh", "-c", "echo hello; echo oops >&2"}"#)
+                .eval()
+                .unwrap();
+            assert_eq!(0, result.get::<_, Integer>("status").unwrap());
+            assert_eq!("hello\n", result.get::<_, String>("stdout").unwrap());
+            assert_eq!("oops\n", result.get::<_, String>("stderr").unwrap());
+        });
+    }
+
+    #[test]
+    fn failure() {
+        with_lua(SandboxLevel::Unrestricted, |lua| {
+            let err = lua
+                .load(r#"em.exec{"sh", "-c", "echo oh no >&2; exit 3"}"#)
+                .exec()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("sh failed"), "{err}");
+            assert!(err.contains("oh no"), "{err}");
+
+            let status: Integer = lua
+                .load(r#"return em.exec({"sh", "-c", "exit 3"}, {check = false}).status"#)
+                .eval()
+                .unwrap();
+            assert_eq!(3, status);
+
+            let err = lua
+                .load(r#"em.exec{"em-no-such-program"}"#)
+                .exec()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("failed to run em-no-such-program"), "{err}");
+
+            let err = lua.load(r#"em.exec{}"#).exec().unwrap_err().to_string();
+            assert!(err.contains("requires a program"), "{err}");
+        });
+    }
+
+    #[test]
+    fn timeout() {
+        with_lua(SandboxLevel::Unrestricted, |lua| {
+            let err = lua
+                .load(r#"em.exec({"sleep", "10"}, {timeout = 0.05})"#)
+                .exec()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("timed out"), "{err}");
+
+            let err = lua
+                .load(r#"em.exec({"true"}, {timeout = -1})"#)
+                .exec()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("positive number of seconds"), "{err}");
+        });
+    }
+}
@@ -0,0 +1,395 @@
+use crate::SandboxLevel;
+use mlua::{Error as MLuaError, Function, Lua, MultiValue, Result as MLuaResult, Table, Value};
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::Arc;
+
+/// Confine file system access by sandboxed code to the given root, or the current directory if
+/// there is none, and to any extra roots which have been declared read-only. At the strict level,
+/// the `io` and `os` functions which touch files have already been removed, but `dofile` and
+/// `require` remain and are confined in the same way. Unrestricted code may access any file.
+///
+/// Relative paths are resolved against the current directory, as that is where Lua looks for them.
+pub(crate) fn restrict_fs(
+    lua: &Lua,
+    level: SandboxLevel,
+    root: Option<&Path>,
+    read_only_roots: &[&str],
+) -> MLuaResult<()> {
+    if level == SandboxLevel::Unrestricted {
+        return Ok(());
+    }
+
+    let cwd = env::current_dir().map_err(MLuaError::external)?;
+    let policy = Arc::new(FsPolicy::new(&cwd, root.unwrap_or(&cwd), read_only_roots));
+    let globals = lua.globals();
+    guard(lua, &globals, "dofile", &policy, |_| {
+        vec![(0, Access::Read)]
+    })?;
+    restrict_searchers(lua, &policy)?;
+
+    if level != SandboxLevel::Standard {
+        return Ok(());
+    }
+
+    let io: Table = globals.get("io")?;
+    let os: Table = globals.get("os")?;
+    guard(lua, &io, "open", &policy, |args| {
+        let mode = args.get(1).and_then(Option::as_deref);
+        vec![(0, Access::for_mode(mode))]
+    })?;
+    guard(lua, &io, "lines", &policy, |_| vec![(0, Access::Read)])?;
+    guard(lua, &io, "input", &policy, |_| vec![(0, Access::Read)])?;
+    guard(lua, &io, "output", &policy, |_| vec![(0, Access::Write)])?;
+    guard(lua, &os, "remove", &policy, |_| vec![(0, Access::Write)])?;
+    guard(lua, &os, "rename", &policy, |_| {
+        vec![(0, Access::Write), (1, Access::Write)]
+    })?;
+    Ok(())
+}
+
+/// Replace the searchers used by `require` so that Lua modules are only loaded from files which
+/// may be read, however `package.path` has been changed. Native modules could do anything, so
+/// they are not loaded at all. Preloaded modules are unaffected.
+fn restrict_searchers(lua: &Lua, policy: &Arc<FsPolicy>) -> MLuaResult<()> {
+    let package: Table = lua.globals().get("package")?;
+    let preload_searcher: Function = package.get::<_, Table>("loaders")?.get(1)?;
+
+    let policy = policy.clone();
+    let lua_searcher = lua.create_function(move |lua, name: String| {
+        let path: String = lua.globals().get::<_, Table>("package")?.get("path")?;
+        let file_name = name.replace('.', &MAIN_SEPARATOR.to_string());
+        let mut tried = String::new();
+        for template in path.split(';').filter(|template| !template.is_empty()) {
+            let candidate = template.replace('?', &file_name);
+            if let Err(e) = policy.check(&candidate, Access::Read) {
+                tried.push_str(&format!("\n\t{e}"));
+                continue;
+            }
+            match fs::read(&candidate) {
+                Ok(src) => {
+                    let module = lua
+                        .load(&src)
+                        .set_name(&format!("@{candidate}"))?
+                        .into_function()?;
+                    return Ok(Value::Function(module));
+                }
+                Err(_) => tried.push_str(&format!("\n\tno file '{candidate}'")),
+            }
+        }
+        Ok(Value::String(lua.create_string(&tried)?))
+    })?;
+
+    let searchers = lua.create_sequence_from([preload_searcher, lua_searcher])?;
+    if package.contains_key("searchers")? {
+        package.set("searchers", searchers.clone())?;
+    }
+    package.set("loaders", searchers)
+}
+
+/// Replace a function which takes paths with one which first checks that they may be accessed.
+/// The given function maps the function's arguments to the indices of those which are paths and
+/// the access which is required to each. Arguments which are not strings, such as file handles,
+/// are passed through unchecked.
+fn guard(
+    lua: &Lua,
+    table: &Table,
+    name: &str,
+    policy: &Arc<FsPolicy>,
+    accesses: fn(&[Option<String>]) -> Vec<(usize, Access)>,
+) -> MLuaResult<()> {
+    let original: Function = table.get(name)?;
+    let original = lua.create_registry_value(original)?;
+    let policy = policy.clone();
+    let guarded = lua.create_function(move |lua, args: MultiValue| {
+        let paths = args
+            .iter()
+            .map(|arg| match arg {
+                Value::String(_) | Value::Integer(_) | Value::Number(_) => Ok(lua
+                    .coerce_string(arg.clone())?
+                    .map(|s| s.to_string_lossy().into_owned())),
+                _ => Ok(None),
+            })
+            .collect::<MLuaResult<Vec<_>>>()?;
+        for (i, access) in accesses(&paths) {
+            if let Some(Some(path)) = paths.get(i) {
+                policy.check(path, access).map_err(MLuaError::SafetyError)?;
+            }
+        }
+        lua.registry_value::<Function>(&original)?
+            .call::<_, MultiValue>(args)
+    })?;
+    table.set(name, guarded)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    /// Returns the access required to open a file with the given mode, as passed to `io.open`.
+    fn for_mode(mode: Option<&str>) -> Self {
+        match mode {
+            None => Self::Read,
+            Some(mode) if mode.starts_with('r') && !mode.contains('+') => Self::Read,
+            Some(_) => Self::Write,
+        }
+    }
+}
+
+/// The parts of the file system which sandboxed code may access.
+#[derive(Debug)]
+struct FsPolicy {
+    /// The directory against which relative paths are resolved.
+    base: PathBuf,
+
+    /// The directory which may be read and written.
+    root: PathBuf,
+
+    /// Further directories which may only be read.
+    read_only_roots: Vec<PathBuf>,
+}
+
+impl FsPolicy {
+    fn new(base: &Path, root: &Path, read_only_roots: &[&str]) -> Self {
+        let base = resolve(base, Path::new("."));
+        let root = resolve(&base, root);
+        let read_only_roots = read_only_roots
+            .iter()
+            .map(|dir| resolve(&base, Path::new(dir)))
+            .collect();
+        Self {
+            base,
+            root,
+            read_only_roots,
+        }
+    }
+
+    fn check(&self, path: &str, access: Access) -> Result<(), String> {
+        let resolved = resolve(&self.base, Path::new(path));
+        if resolved.starts_with(&self.root) {
+            return Ok(());
+        }
+        if self
+            .read_only_roots
+            .iter()
+            .any(|dir| resolved.starts_with(dir))
+        {
+            return match access {
+                Access::Read => Ok(()),
+                Access::Write => Err(format!("{path} is read-only to the sandbox")),
+            };
+        }
+        Err(format!("{path} is outside the sandbox"))
+    }
+}
+
+/// Find where a path relative to the given directory really points, following any symbolic links
+/// so that they cannot be used to escape the sandbox. Paths which do not exist yet are resolved
+/// as far as possible.
+fn resolve(dir: &Path, path: &Path) -> PathBuf {
+    let path = dir.join(path);
+    if let Ok(resolved) = path.canonicalize() {
+        return resolved;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => resolve(dir, parent).join(name),
+        _ => normalise(&path),
+    }
+}
+
+/// Remove `.` and `..` components from a path without consulting the file system.
+fn normalise(path: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                ret.pop();
+            }
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{error::Error, fs};
+
+    #[test]
+    fn policy() -> Result<(), Box<dyn Error>> {
+        let doc_dir = tempfile::tempdir()?;
+        let assets = tempfile::tempdir()?;
+        let elsewhere = tempfile::tempdir()?;
+        fs::write(doc_dir.path().join("main.em"), "")?;
+        fs::write(assets.path().join("logo.svg"), "")?;
+        fs::write(elsewhere.path().join("secret"), "")?;
+
+        let assets_path = assets.path().to_str().unwrap();
+        let policy = FsPolicy::new(doc_dir.path(), doc_dir.path(), &[assets_path]);
+        let in_assets = |name: &str| assets.path().join(name).to_str().unwrap().to_owned();
+        let secret = elsewhere.path().join("secret");
+        let secret = secret.to_str().unwrap();
+
+        assert_eq!(Ok(()), policy.check("main.em", Access::Read));
+        assert_eq!(Ok(()), policy.check("main.em", Access::Write));
+        assert_eq!(Ok(()), policy.check("new/file.txt", Access::Write));
+        assert_eq!(Ok(()), policy.check("./a/../main.em", Access::Read));
+
+        assert_eq!(Ok(()), policy.check(&in_assets("logo.svg"), Access::Read));
+        assert_eq!(Ok(()), policy.check(&in_assets("missing"), Access::Read));
+        assert_eq!(
+            Err(format!(
+                "{} is read-only to the sandbox",
+                in_assets("logo.svg")
+            )),
+            policy.check(&in_assets("logo.svg"), Access::Write)
+        );
+
+        assert_eq!(
+            Err(format!("{secret} is outside the sandbox")),
+            policy.check(secret, Access::Read)
+        );
+        let escape = format!("{}/../../{secret}", assets_path);
+        assert_eq!(
+            Err(format!("{escape} is outside the sandbox")),
+            policy.check(&escape, Access::Read)
+        );
+        assert_eq!(
+            Err("../main.em is outside the sandbox".to_owned()),
+            policy.check("../main.em", Access::Read)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn root_outside_base() -> Result<(), Box<dyn Error>> {
+        let base = tempfile::tempdir()?;
+        let doc_dir = tempfile::tempdir()?;
+        fs::create_dir(base.path().join("assets"))?;
+        fs::write(base.path().join("main.em"), "")?;
+        fs::write(doc_dir.path().join("main.em"), "")?;
+
+        let policy = FsPolicy::new(base.path(), doc_dir.path(), &["assets"]);
+        let in_doc_dir = doc_dir.path().join("main.em");
+        assert_eq!(
+            Ok(()),
+            policy.check(in_doc_dir.to_str().unwrap(), Access::Write)
+        );
+        assert_eq!(Ok(()), policy.check("assets/logo.svg", Access::Read));
+        assert_eq!(
+            Err("main.em is outside the sandbox".to_owned()),
+            policy.check("main.em", Access::Read)
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks() -> Result<(), Box<dyn Error>> {
+        let doc_dir = tempfile::tempdir()?;
+        let elsewhere = tempfile::tempdir()?;
+        std::os::unix::fs::symlink(elsewhere.path(), doc_dir.path().join("link"))?;
+
+        let policy = FsPolicy::new(doc_dir.path(), doc_dir.path(), &[]);
+        assert_eq!(
+            Err("link/secret is outside the sandbox".to_owned()),
+            policy.check("link/secret", Access::Read)
+        );
+        assert_eq!(
+            Err("link/../new.txt is outside the sandbox".to_owned()),
+            policy.check("link/../new.txt", Access::Write)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn modes() {
+        for (mode, access) in [
+            (None, Access::Read),
+            (Some("r"), Access::Read),
+            (Some("rb"), Access::Read),
+            (Some("r+"), Access::Write),
+            (Some("w"), Access::Write),
+            (Some("a"), Access::Write),
+        ] {
+            assert_eq!(access, Access::for_mode(mode), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn guarded_functions() -> Result<(), Box<dyn Error>> {
+        let doc_dir = tempfile::tempdir()?;
+        let assets = tempfile::tempdir()?;
+        fs::write(doc_dir.path().join("main.em"), "hello")?;
+        fs::write(assets.path().join("logo.svg"), "<svg/>")?;
+        let assets_path = assets.path().to_str().unwrap();
+        let logo = assets.path().join("logo.svg");
+        let logo = logo.to_str().unwrap();
+        let main = doc_dir.path().join("main.em");
+        let main = main.to_str().unwrap();
+
+        for level in SandboxLevel::input_levels() {
+            let lua = Lua::new();
+            restrict_fs(&lua, level, Some(doc_dir.path()), &[assets_path])?;
+
+            let read = |path: &str| {
+                lua.load(&format!(
+                    "local f = assert(io.open({path:?})); local s = f:read('*a'); f:close(); return s"
+                ))
+                .eval::<String>()
+            };
+            assert_eq!("hello", read(main)?);
+            assert_eq!("<svg/>", read(logo)?);
+
+            let write = lua.load(&format!("return io.open({logo:?}, 'w')")).exec();
+            let remove = lua.load(&format!("os.remove({logo:?})")).exec();
+            if level == SandboxLevel::Standard {
+                for result in [write, remove] {
+                    let err = result.unwrap_err().to_string();
+                    assert!(err.contains("is read-only to the sandbox"), "{err}");
+                }
+                assert!(Path::new(logo).exists());
+            } else {
+                write?;
+                remove?;
+                fs::write(logo, "<svg/>")?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn require() -> Result<(), Box<dyn Error>> {
+        let doc_dir = tempfile::tempdir()?;
+        let elsewhere = tempfile::tempdir()?;
+        fs::write(doc_dir.path().join("allowed.lua"), "return 42")?;
+        fs::write(elsewhere.path().join("secret.lua"), "return 'secret'")?;
+        let path = format!(
+            "{};{}",
+            doc_dir.path().join("?.lua").to_str().unwrap(),
+            elsewhere.path().join("?.lua").to_str().unwrap(),
+        );
+
+        for level in [SandboxLevel::Standard, SandboxLevel::Strict] {
+            let lua = Lua::new();
+            restrict_fs(&lua, level, Some(doc_dir.path()), &[])?;
+            lua.load(&format!("package.path = {path:?}")).exec()?;
+
+            assert_eq!(42, lua.load("return require 'allowed'").eval::<i64>()?);
+
+            let err = lua
+                .load("return require 'secret'")
+                .exec()
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("secret.lua is outside the sandbox"), "{err}");
+        }
+        Ok(())
+    }
+}
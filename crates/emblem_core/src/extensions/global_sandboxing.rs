@@ -6,6 +6,27 @@ pub(crate) fn restrict_globals(lua: &Lua, level: SandboxLevel) -> MLuaResult<()>
     restrict_table(lua, level, lua.globals(), &CONSTRAINTS)
 }
 
+/// Allow sandboxed code to read only the given environment variables through `os.getenv`.
+/// Unrestricted code may read any variable.
+pub(crate) fn restrict_env(lua: &Lua, level: SandboxLevel, allowlist: &[&str]) -> MLuaResult<()> {
+    if level <= SandboxLevel::Unrestricted {
+        return Ok(());
+    }
+
+    let allowlist: Vec<String> = allowlist.iter().map(|name| name.to_string()).collect();
+    let getenv = lua.create_function(move |_, name: String| {
+        if !allowlist.contains(&name) {
+            return Err(MLuaError::SafetyError(format!(
+                "environment variable {name} is not in the allowlist"
+            )));
+        }
+        Ok(std::env::var(&name).ok())
+    })?;
+
+    let os: Table = lua.globals().get("os")?;
+    os.set("getenv", getenv)
+}
+
 fn restrict_table(
     lua: &Lua,
     level: SandboxLevel,
@@ -181,18 +202,18 @@ const CONSTRAINTS: Map<&'static str, Constraint> = phf_map! {
     "io" => Constraint::Table(phf_map!{
         "close"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
         "flush"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
-        "input"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
-        "lines"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
-        "open"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
-        "output"  => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
+        "input"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
+        "lines"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
+        "open"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
+        "output"  => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
         "popen"   => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::NilFunc)),
-        "read"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
+        "read"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
         "stderr"  => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::Nil)),
         "stdin"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::Nil)),
         "stdout"  => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::Nil)),
         "tmpfile" => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
         "type"    => Constraint::AtMost(SandboxLevel::Strict, None),
-        "write"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with custom one which only allows in current dir!
+        "write"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
     }),
     "jit" => Constraint::Table(phf_map!{
         "arch"        => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::Nil)),
@@ -247,8 +268,8 @@ const CONSTRAINTS: Map<&'static str, Constraint> = phf_map! {
         "execute"   => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::NilFunc)),
         "exit"      => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::ErrFunc("os.exit"))),
         "getenv"    => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::NilFunc)),
-        "remove"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with sandboxed one!
-        "rename"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)), // TODO(kcza): replace with sandboxed one!
+        "remove"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
+        "rename"    => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
         "setlocale" => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::NilFunc)),
         "time"      => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
         "tmpname"   => Constraint::AtMost(SandboxLevel::Standard, Some(Replacement::NilFunc)),
@@ -258,7 +279,7 @@ const CONSTRAINTS: Map<&'static str, Constraint> = phf_map! {
         "cpath"      => Constraint::AtMost(SandboxLevel::Strict, None),
         "loaded"     => Constraint::AtMost(SandboxLevel::Strict, None),
         "loaders"    => Constraint::AtMost(SandboxLevel::Strict, None),
-        "loadlib"    => Constraint::AtMost(SandboxLevel::Strict, None),
+        "loadlib"    => Constraint::AtMost(SandboxLevel::Unrestricted, Some(Replacement::NilFunc)),
         "path"       => Constraint::AtMost(SandboxLevel::Strict, None),
         "preload"    => Constraint::AtMost(SandboxLevel::Strict, None),
         "searchers"  => Constraint::AtMost(SandboxLevel::Strict, None),
@@ -445,6 +466,41 @@ mod test {
         check_replacements(&lua, lua.globals(), &CONSTRAINTS).unwrap()
     }
 
+    #[test]
+    fn env_allowlist() -> Result<(), Box<dyn Error>> {
+        std::env::set_var("EMBLEM_TEST_ALLOWED", "allowed");
+        std::env::set_var("EMBLEM_TEST_DENIED", "denied");
+
+        for level in SandboxLevel::input_levels() {
+            let mut ctx = Context::test_new();
+            ctx.lua_params_mut().set_sandbox_level(level);
+            ctx.lua_params_mut()
+                .set_env_allowlist(vec!["EMBLEM_TEST_ALLOWED", "EMBLEM_TEST_UNSET"]);
+            let ext_state = ctx.extension_state()?;
+            let lua = ext_state.lua();
+
+            let getenv = |name: &str| {
+                lua.load(&format!("return os.getenv({name:?})"))
+                    .eval::<Option<String>>()
+            };
+            assert_eq!(Some("allowed".into()), getenv("EMBLEM_TEST_ALLOWED")?);
+            assert_eq!(None, getenv("EMBLEM_TEST_UNSET")?);
+
+            let denied = getenv("EMBLEM_TEST_DENIED");
+            if level == SandboxLevel::Unrestricted {
+                assert_eq!(Some("denied".into()), denied?);
+            } else {
+                let err = denied.unwrap_err().to_string();
+                assert!(
+                    err.contains("environment variable EMBLEM_TEST_DENIED is not in the allowlist"),
+                    "{err}"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn replacements_provided_correctly() {
         fn check_replacements(constraints: &Map<&'static str, Constraint>) {
@@ -1,11 +1,14 @@
 mod em;
 mod env_extras;
+pub(crate) mod exec;
+mod fs_sandboxing;
 mod global_sandboxing;
 mod preload_decls;
 mod preload_sandboxing;
+mod std_streams;
 
 use crate::{
-    context::{LuaParameters, ResourceLimit, SandboxLevel},
+    context::{CancellationToken, LuaParameters, ResourceLimit, SandboxLevel},
     Context,
 };
 use em::Em;
@@ -29,6 +32,7 @@ const EVENT_LISTENERS_RKEY: &str = emblem_registry_key!("events");
 
 pub struct ExtensionState<'em> {
     lua: Lua,
+    cancellation_token: CancellationToken,
     phantom: PhantomData<&'em Context<'em>>,
 }
 
@@ -48,22 +52,35 @@ impl<'em> ExtensionState<'em> {
         preload_sandboxing::restrict_preload(&lua, sandbox_level)?;
         env_extras::import_extras(&lua)?;
         global_sandboxing::restrict_globals(&lua, sandbox_level)?;
-
-        Self::insert_safety_hook(&lua, params)?;
+        global_sandboxing::restrict_env(&lua, sandbox_level, params.env_allowlist())?;
+        std_streams::redirect_stdout(&lua)?;
+        fs_sandboxing::restrict_fs(
+            &lua,
+            sandbox_level,
+            params.sandbox_root(),
+            params.read_only_roots(),
+        )?;
+
+        Self::insert_safety_hook(&lua, params, ctx.cancellation_token().clone())?;
         Self::setup_event_listeners(&lua)?;
 
-        lua.globals().set("em", Em::new())?;
+        lua.globals().set("em", Em::new(sandbox_level))?;
         // TODO(kcza): set args
 
         lua.load(STD).exec()?;
 
         Ok(ExtensionState {
             lua,
+            cancellation_token: ctx.cancellation_token().clone(),
             phantom: PhantomData,
         })
     }
 
-    fn insert_safety_hook(lua: &Lua, params: &LuaParameters) -> MLuaResult<()> {
+    fn insert_safety_hook(
+        lua: &Lua,
+        params: &LuaParameters,
+        cancellation_token: CancellationToken,
+    ) -> MLuaResult<()> {
         const INSTRUCTION_INTERVAL: u32 = 1;
 
         let max_mem = params.max_mem();
@@ -72,6 +89,10 @@ impl<'em> ExtensionState<'em> {
         lua.set_hook(
             HookTriggers::every_nth_instruction(INSTRUCTION_INTERVAL),
             move |lua, _debug| {
+                if cancellation_token.is_cancelled() {
+                    return Err(MLuaError::SafetyError("build cancelled".into()));
+                }
+
                 if let ResourceLimit::Limited(max_mem) = max_mem {
                     if lua.used_memory() >= max_mem {
                         return Err(MLuaError::SafetyError("too much memory used".into()));
@@ -113,6 +134,8 @@ impl<'em> ExtensionState<'em> {
         &self.lua
     }
 
+    /// Add a listener for the given event. Listeners are called in the order they were added,
+    /// so that builds are reproducible.
     pub fn add_listener(&self, event: EventType, listener: Value) -> MLuaResult<()> {
         if !callable(&listener) {
             return Err(MLuaError::RuntimeError(format!(
@@ -138,6 +161,9 @@ impl<'em> ExtensionState<'em> {
         };
 
         for listener in event_listeners.sequence_values::<Value>() {
+            if self.cancellation_token.is_cancelled() {
+                return Err(MLuaError::SafetyError("build cancelled".into()));
+            }
             self.call_listener(listener?, event)?;
         }
 
@@ -315,7 +341,7 @@ mod test {
     use mlua::chunk;
 
     use super::*;
-    use std::error::Error;
+    use std::{cell::RefCell, error::Error, rc::Rc};
 
     #[test]
     fn std_tests() {
@@ -356,6 +382,33 @@ mod test {
         );
     }
 
+    #[test]
+    fn listener_order() -> Result<(), Box<dyn Error>> {
+        let ctx = Context::test_new();
+        let ext_state = ctx.extension_state()?;
+        let lua = ext_state.lua();
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        for i in [3, 1, 4, 1, 5, 9, 2, 6] {
+            let order = order.clone();
+            ext_state.add_listener(
+                EventType::IterStart,
+                Value::Function(lua.create_function(move |_, _: Value| {
+                    order.borrow_mut().push(i);
+                    Ok(())
+                })?),
+            )?;
+        }
+
+        for iter in 1..=3 {
+            order.borrow_mut().clear();
+            ext_state.handle(Event::IterStart { iter })?;
+            assert_eq!(vec![3, 1, 4, 1, 5, 9, 2, 6], *order.borrow());
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn steps_limited() -> Result<(), Box<dyn Error>> {
         let threshold = 10000;
@@ -386,6 +439,37 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn cancellation() -> Result<(), Box<dyn Error>> {
+        let token = CancellationToken::new();
+        let ctx = {
+            let mut ctx = Context::test_new();
+            ctx.set_cancellation_token(token.clone());
+            ctx
+        };
+        let ext_state = ctx.extension_state()?;
+        let lua = ext_state.lua();
+        lua.globals().set(
+            "cancel",
+            lua.create_function(move |_, ()| {
+                token.cancel();
+                Ok(())
+            })?,
+        )?;
+
+        let result = lua
+            .load(chunk! {
+                jit.off();
+                cancel();
+                while true do end
+            })
+            .exec();
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("build cancelled"), "{err}");
+
+        Ok(())
+    }
+
     #[test]
     fn memory_limited() -> Result<(), Box<dyn Error>> {
         let threshold = 100000;
@@ -0,0 +1,61 @@
+use mlua::{Error as MLuaError, Function, Lua, MultiValue, Result as MLuaResult, Table, Value};
+use std::io::{self, Write};
+
+/// Send anything extensions print to stderr alongside diagnostics, so that it cannot corrupt a
+/// document written to stdout. This replaces `print` and, where the sandbox allows io, replaces
+/// `io.stdout` with `io.stderr` and makes it the default output file used by `io.write`.
+pub(crate) fn redirect_stdout(lua: &Lua) -> MLuaResult<()> {
+    let globals = lua.globals();
+    globals.set("print", lua.create_function(print)?)?;
+
+    let io: Table = globals.get("io")?;
+    if let stderr @ Value::UserData(_) = io.get("stderr")? {
+        io.get::<_, Function>("output")?
+            .call::<_, ()>(stderr.clone())?;
+        io.set("stdout", stderr)?;
+    }
+    Ok(())
+}
+
+fn print(lua: &Lua, args: MultiValue) -> MLuaResult<()> {
+    let tostring: Function = lua.globals().get("tostring")?;
+    let mut line = Vec::new();
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            line.push(b'\t');
+        }
+        line.extend_from_slice(tostring.call::<_, mlua::String>(arg)?.as_bytes());
+    }
+    line.push(b'\n');
+    io::stderr().write_all(&line).map_err(MLuaError::external)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::context::{Context, SandboxLevel};
+    use std::error::Error;
+
+    #[test]
+    fn redirected() -> Result<(), Box<dyn Error>> {
+        for level in SandboxLevel::input_levels() {
+            let ctx = {
+                let mut ctx = Context::test_new();
+                ctx.lua_params_mut().set_sandbox_level(level);
+                ctx
+            };
+            let ext_state = ctx.extension_state()?;
+            let lua = ext_state.lua();
+
+            lua.load("print('hello', 1, nil, {})").exec()?;
+            assert!(!lua
+                .load("return pcall(print, setmetatable({}, { __tostring = function() end }))")
+                .eval::<bool>()?);
+
+            if level < SandboxLevel::Strict {
+                assert!(lua.load("return io.output() == io.stderr").eval::<bool>()?);
+                assert!(lua.load("return io.stdout == io.stderr").eval::<bool>()?);
+            }
+        }
+        Ok(())
+    }
+}
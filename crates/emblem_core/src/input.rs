@@ -0,0 +1,43 @@
+use crate::args::ArgPath;
+use std::{fmt, path};
+
+/// A source of emblem markup to be processed.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Input {
+    /// Read from standard input.
+    Stdin,
+
+    /// Read from a file on disk.
+    File(path::PathBuf),
+
+    /// Read from source held in memory, reporting problems against the given name.
+    Source { name: String, content: String },
+}
+
+impl Input {
+    pub fn source(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self::Source {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+}
+
+impl From<ArgPath> for Input {
+    fn from(path: ArgPath) -> Self {
+        match path {
+            ArgPath::Stdio => Self::Stdin,
+            ArgPath::Path(p) => Self::File(p),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdin => write!(f, "-"),
+            Self::File(p) => write!(f, "{}", p.to_str().unwrap_or("(invalid path)")),
+            Self::Source { name, .. } => write!(f, "{name}"),
+        }
+    }
+}
@@ -9,12 +9,17 @@ pub mod args;
 pub mod ast;
 pub mod build;
 pub mod context;
+pub mod drivers;
 pub mod explain;
 mod extensions;
+pub mod input;
 pub mod lint;
 pub mod parser;
 mod path;
+pub mod repl;
 mod repo;
+pub mod stats;
+pub mod style;
 mod util;
 mod version;
 
@@ -25,16 +30,22 @@ pub use crate::{
             doc::{Doc, DocElem},
             Typesetter,
         },
-        Builder,
+        BuildStats, Builder,
     },
-    context::{file_name::FileName, Context, ResourceLimit, SandboxLevel},
+    context::{file_name::FileName, Context, ResourceLimit, SandboxLevel, Severity},
     explain::Explainer,
     extensions::ExtensionState,
+    input::Input,
     lint::Linter,
     log::{Log, Verbosity},
+    repl::Repl,
+    stats::StatsReporter,
     version::Version,
 };
 
+#[cfg(feature = "ast-debug")]
+pub use crate::ast::AstDumper;
+
 use derive_new::new;
 
 pub trait Action {
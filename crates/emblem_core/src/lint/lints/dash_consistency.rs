@@ -0,0 +1,331 @@
+use crate::ast::parsed::Content;
+use crate::ast::Dash;
+use crate::lint::{Fix, Lint};
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+
+/// Checks that parenthetical dashes are written the same way throughout a document. The
+/// convention may be configured, otherwise the first spaced en dash, spaced em dash or unspaced
+/// em dash sets it. Unspaced en dashes are taken to be ranges, such as `1--5`, and so are not
+/// checked.
+#[derive(new)]
+pub struct DashConsistency<'i> {
+    configured: Option<DashConvention>,
+
+    #[new(default)]
+    prev_spaced: Option<bool>,
+
+    #[new(default)]
+    prev_whitespace: Option<Location<'i>>,
+
+    #[new(default)]
+    pending: Option<PendingDash<'i>>,
+
+    #[new(default)]
+    convention: Option<(DashStyle, Location<'i>)>,
+
+    #[new(default)]
+    fixes: Vec<Fix>,
+}
+
+/// The ways in which parenthetical dashes may be written.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DashConvention {
+    /// An en dash with a space either side, ` -- `.
+    SpacedEn,
+
+    /// An em dash with a space either side, ` --- `.
+    SpacedEm,
+
+    /// An em dash with no surrounding space, `---`.
+    UnspacedEm,
+}
+
+impl DashConvention {
+    fn style(self) -> DashStyle {
+        match self {
+            Self::SpacedEn => DashStyle {
+                dash: Dash::En,
+                spaced: true,
+            },
+            Self::SpacedEm => DashStyle {
+                dash: Dash::Em,
+                spaced: true,
+            },
+            Self::UnspacedEm => DashStyle {
+                dash: Dash::Em,
+                spaced: false,
+            },
+        }
+    }
+}
+
+/// A dash whose following content has not been seen yet.
+struct PendingDash<'i> {
+    dash: Dash,
+    spaced_before: bool,
+    loc: Location<'i>,
+
+    /// Where a replacement for this dash and its surrounding space would start.
+    fix_start: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct DashStyle {
+    dash: Dash,
+    spaced: bool,
+}
+
+impl DashStyle {
+    fn describe(&self) -> &'static str {
+        match (self.dash, self.spaced) {
+            (Dash::En, true) => "spaced en dash",
+            (Dash::En, false) => "unspaced en dash",
+            (Dash::Em, true) => "spaced em dash",
+            (Dash::Em, false) => "unspaced em dash",
+            (Dash::Hyphen, _) => "hyphen",
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match (self.dash, self.spaced) {
+            (Dash::En, true) => " -- ",
+            (Dash::En, false) => "--",
+            (Dash::Em, true) => " --- ",
+            (Dash::Em, false) => "---",
+            (Dash::Hyphen, _) => "-",
+        }
+    }
+}
+
+impl<'i> DashConsistency<'i> {
+    fn check(&mut self, pending: PendingDash<'i>, after: Option<&Location<'i>>) -> Vec<Log<'i>> {
+        let spaced_after = after.is_some();
+        if pending.spaced_before != spaced_after {
+            return vec![];
+        }
+
+        let style = DashStyle {
+            dash: pending.dash,
+            spaced: spaced_after,
+        };
+        if style.dash == Dash::En && !style.spaced {
+            return vec![];
+        }
+
+        let loc = pending.loc;
+        let found = Src::new(&loc)
+            .with_annotation(Note::warn(&loc, format!("found {} here", style.describe())));
+        let (convention, log) = match (self.configured, &self.convention) {
+            (Some(configured), _) => {
+                let convention = configured.style();
+                let log = Log::warn("parenthetical dash does not follow the configured convention")
+                    .with_src(found)
+                    .with_note(format!(
+                        "parenthetical dashes are configured to be {}es",
+                        convention.describe()
+                    ));
+                (convention, log)
+            }
+            (None, Some((convention, convention_loc))) => {
+                let log = Log::warn("parenthetical dashes are used inconsistently")
+                    .with_src(found)
+                    .with_src(Src::new(convention_loc).with_annotation(Note::info(
+                        convention_loc,
+                        format!("{} first used here", convention.describe()),
+                    )));
+                (*convention, log)
+            }
+            (None, None) => {
+                self.convention = Some((style, loc));
+                return vec![];
+            }
+        };
+        if style == convention {
+            return vec![];
+        }
+
+        let fix_end = after.unwrap_or(&loc).end().index;
+        self.fixes
+            .push(Fix::new(pending.fix_start..fix_end, convention.source()));
+
+        vec![log.with_help(format!("use a {} instead", convention.describe()))]
+    }
+}
+
+impl<'i> Lint<'i> for DashConsistency<'i> {
+    fn id(&self) -> &'static str {
+        "dash-consistency"
+    }
+
+    fn analyse(&mut self, content: &Content<'i>) -> Vec<Log<'i>> {
+        let whitespace = match content {
+            Content::Whitespace { loc, .. } => Some(loc),
+            _ => None,
+        };
+
+        let problems = match self.pending.take() {
+            Some(pending) => self.check(pending, whitespace),
+            None => vec![],
+        };
+
+        match content {
+            Content::Dash { dash, loc } if *dash != Dash::Hyphen => {
+                if let Some(spaced_before) = self.prev_spaced {
+                    let fix_start = self.prev_whitespace.as_ref().unwrap_or(loc).start().index;
+                    self.pending = Some(PendingDash {
+                        dash: *dash,
+                        spaced_before,
+                        loc: loc.clone(),
+                        fix_start,
+                    });
+                }
+            }
+            Content::Shebang { .. }
+            | Content::Command { .. }
+            | Content::Sugar(_)
+            | Content::Word { .. }
+            | Content::Whitespace { .. }
+            | Content::Dash { .. }
+            | Content::Glue { .. }
+            | Content::SpiltGlue { .. }
+            | Content::Verbatim { .. }
+            | Content::Comment { .. }
+            | Content::MultiLineComment { .. } => {}
+        }
+        self.prev_spaced = Some(whitespace.is_some());
+        self.prev_whitespace = whitespace.cloned();
+
+        problems
+    }
+
+    fn fixes(&mut self) -> Vec<Fix> {
+        std::mem::take(&mut self.fixes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::lints::{fix, test::LintTest};
+
+    #[test]
+    fn lint() {
+        let tests = [
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 0,
+                matches: vec![],
+                src: "",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 0,
+                matches: vec![],
+                src: "foo --- bar --- baz",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 0,
+                matches: vec![],
+                src: "foo---bar, pages 1--5, foo---bar",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 0,
+                matches: vec![],
+                src: "well-known foo -- bar, pages 1--5 -- baz",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 1,
+                matches: vec![
+                    "parenthetical dashes are used inconsistently",
+                    ":1:17-19: found unspaced em dash here",
+                    ":1:5-7: spaced em dash first used here",
+                    "use a spaced em dash instead",
+                ],
+                src: "foo --- bar, baz---qux",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 2,
+                matches: vec![
+                    "parenthetical dashes are used inconsistently",
+                    "spaced en dash first used here",
+                ],
+                src: "foo -- bar\n\nbaz --- qux _quux_---corge",
+            },
+            LintTest {
+                lint: DashConsistency::new(None),
+                num_problems: 0,
+                matches: vec![],
+                src: "foo ---bar, baz--- qux",
+            },
+        ];
+
+        for test in tests {
+            test.run();
+        }
+    }
+    #[test]
+    fn configured() {
+        let tests = [
+            LintTest {
+                lint: DashConsistency::new(Some(DashConvention::SpacedEn)),
+                num_problems: 0,
+                matches: vec![],
+                src: "foo -- bar, pages 1--5 -- baz",
+            },
+            LintTest {
+                lint: DashConsistency::new(Some(DashConvention::SpacedEn)),
+                num_problems: 2,
+                matches: vec![
+                    "parenthetical dash does not follow the configured convention",
+                    "found (spaced|unspaced) em dash here",
+                    "parenthetical dashes are configured to be spaced en dashes",
+                    "use a spaced en dash instead",
+                ],
+                src: "foo --- bar---baz",
+            },
+            LintTest {
+                lint: DashConsistency::new(Some(DashConvention::UnspacedEm)),
+                num_problems: 1,
+                matches: vec![":1:5-7: found spaced em dash here"],
+                src: "foo --- bar---baz",
+            },
+            LintTest {
+                lint: DashConsistency::new(Some(DashConvention::SpacedEm)),
+                num_problems: 0,
+                matches: vec![],
+                src: "foo --- bar",
+            },
+        ];
+
+        for test in tests {
+            test.run();
+        }
+    }
+
+    #[test]
+    fn fixes() {
+        assert_eq!(None, fix("foo --- bar --- baz", None));
+        assert_eq!(None, fix("pages 1--5", Some(DashConvention::UnspacedEm)));
+        assert_eq!(
+            Some("foo --- bar --- baz --- qux".into()),
+            fix("foo --- bar---baz\t--  qux", None)
+        );
+        assert_eq!(
+            Some("foo -- bar -- baz, pages 1--5".into()),
+            fix(
+                "foo---bar --- baz, pages 1--5",
+                Some(DashConvention::SpacedEn)
+            )
+        );
+        assert_eq!(
+            Some("_foo---bar_---baz".into()),
+            fix("_foo --- bar_ -- baz", Some(DashConvention::UnspacedEm))
+        );
+    }
+}
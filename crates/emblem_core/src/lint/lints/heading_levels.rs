@@ -0,0 +1,142 @@
+use crate::ast::parsed::{Content, Sugar};
+use crate::lint::Lint;
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+
+/// Flags headings which skip levels, as readers relying on document structure (for example,
+/// those using screen readers) use heading levels to navigate.
+#[derive(new)]
+pub struct HeadingLevels {
+    #[new(default)]
+    prev_level: Option<usize>,
+}
+
+impl HeadingLevels {
+    fn heading_level<'i>(content: &Content<'i>) -> Option<(usize, Location<'i>)> {
+        match content {
+            Content::Sugar(Sugar::Heading {
+                level,
+                invocation_loc,
+                ..
+            }) => Some((*level, invocation_loc.clone())),
+            Content::Command {
+                qualifier: None,
+                name,
+                invocation_loc,
+                ..
+            } => {
+                let level = match name.as_str() {
+                    "h1" => 1,
+                    "h2" => 2,
+                    "h3" => 3,
+                    "h4" => 4,
+                    "h5" => 5,
+                    "h6" => 6,
+                    _ => return None,
+                };
+                Some((level, invocation_loc.clone()))
+            }
+            Content::Shebang { .. }
+            | Content::Command { .. }
+            | Content::Sugar(_)
+            | Content::Word { .. }
+            | Content::Whitespace { .. }
+            | Content::Dash { .. }
+            | Content::Glue { .. }
+            | Content::SpiltGlue { .. }
+            | Content::Verbatim { .. }
+            | Content::Comment { .. }
+            | Content::MultiLineComment { .. } => None,
+        }
+    }
+}
+
+impl<'i> Lint<'i> for HeadingLevels {
+    fn id(&self) -> &'static str {
+        "heading-levels"
+    }
+
+    fn analyse(&mut self, content: &Content<'i>) -> Vec<Log<'i>> {
+        let Some((level, loc)) = Self::heading_level(content) else {
+            return vec![];
+        };
+
+        let ret = match self.prev_level {
+            Some(prev_level) if level > prev_level + 1 => {
+                vec![
+                    Log::warn(format!("heading skips from level {prev_level} to {level}"))
+                        .with_src(Src::new(&loc).with_annotation(Note::info(&loc, "found here")))
+                        .with_help(format!("try using a level-{} heading", prev_level + 1)),
+                ]
+            }
+            _ => vec![],
+        };
+
+        self.prev_level = Some(level);
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lint::lints::test::LintTest;
+
+    #[test]
+    fn lint() {
+        let tests = [
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 0,
+                matches: vec![],
+                src: "",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 0,
+                matches: vec![],
+                src: "# foo\n\n## bar\n\n### baz\n\n# qux",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 0,
+                matches: vec![],
+                src: "### foo\n\n# bar\n\n## baz",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 1,
+                matches: vec![
+                    "heading skips from level 1 to 3",
+                    ":3:1-3: found here",
+                    "try using a level-2 heading",
+                ],
+                src: "# foo\n\n### bar",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 1,
+                matches: vec!["heading skips from level 2 to 4", ":3:1-3: found here"],
+                src: ".h2: foo\n\n.h4: bar",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 2,
+                matches: vec!["heading skips from level"],
+                src: "# foo\n\n### bar\n\n###### baz",
+            },
+            LintTest {
+                lint: HeadingLevels::new(),
+                num_problems: 0,
+                matches: vec![],
+                src: "# foo\n\n.ext.h3: bar",
+            },
+        ];
+
+        for test in tests {
+            test.run();
+        }
+    }
+}
@@ -1,17 +1,22 @@
 mod attr_ordering;
 mod command_naming;
+mod dash_consistency;
 mod duplicate_attrs;
 mod emph_delimiters;
 mod empty_attrs;
+mod heading_levels;
 mod num_args;
 mod num_attrs;
 mod num_pluses;
 mod spilt_glue;
 mod sugar_usage;
 
-use super::Lints;
+pub use dash_consistency::DashConvention;
 
-pub fn lints<'i>() -> Lints<'i> {
+use super::{Fix, Lintable, Lints};
+use crate::{parser, FileName};
+
+pub fn lints<'i>(dashes: Option<DashConvention>) -> Lints<'i> {
     macro_rules! lints {
         ($($lint:expr),* $(,)?) => {
             vec![
@@ -23,9 +28,11 @@ pub fn lints<'i>() -> Lints<'i> {
     lints![
         attr_ordering::AttrOrdering::new(),
         command_naming::CommandNaming::new(),
+        dash_consistency::DashConsistency::new(dashes),
         duplicate_attrs::DuplicateAttrs::new(),
         emph_delimiters::EmphDelimiters::new(),
         empty_attrs::EmptyAttrs::new(),
+        heading_levels::HeadingLevels::new(),
         num_args::NumArgs::new(),
         num_attrs::NumAttrs::new(),
         num_pluses::NumPluses::new(),
@@ -34,14 +41,39 @@ pub fn lints<'i>() -> Lints<'i> {
     ]
 }
 
+/// Apply the fixes suggested by lints to the given source. Returns `None` if there is nothing to
+/// fix or the source cannot be parsed.
+pub fn fix(src: &str, dashes: Option<DashConvention>) -> Option<String> {
+    let file = parser::parse(FileName::default(), src).ok()?;
+
+    let mut lints = lints(dashes);
+    file.lint(&mut lints, &mut Vec::new());
+
+    let mut fixes: Vec<Fix> = lints.iter_mut().flat_map(|lint| lint.fixes()).collect();
+    if fixes.is_empty() {
+        return None;
+    }
+    fixes.sort_by_key(|fix| fix.range.start);
+
+    let mut fixed = String::with_capacity(src.len());
+    let mut copied = 0;
+    for fix in fixes {
+        // Overlapping fixes are left for the next run.
+        if fix.range.start < copied {
+            continue;
+        }
+        fixed.push_str(&src[copied..fix.range.start]);
+        fixed.push_str(fix.replacement);
+        copied = fix.range.end;
+    }
+    fixed.push_str(&src[copied..]);
+    Some(fixed)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{
-        lint::{Lint, Lintable},
-        parser::parse,
-        FileName,
-    };
+    use crate::{lint::Lint, parser::parse};
     use lazy_static::lazy_static;
     use regex::Regex;
     use std::collections::HashSet;
@@ -52,7 +84,7 @@ mod test {
             static ref VALID_ID: Regex = Regex::new(r"^[a-z-]+$").unwrap();
         }
 
-        let lints = lints();
+        let lints = lints(None);
         let ids = lints.iter().map(|l| l.id()).collect::<Vec<_>>();
 
         for id in &ids {
@@ -65,14 +97,14 @@ mod test {
     #[test]
     fn unique_ids() {
         let mut ids = HashSet::new();
-        for lint in lints() {
+        for lint in lints(None) {
             assert!(ids.insert(lint.id()), "id {:?} is not unique", lint.id());
         }
     }
 
     pub struct LintTest<'i, L>
     where
-        L: Lint<'i> + 'static,
+        L: Lint<'i> + 'i,
     {
         pub lint: L,
         pub num_problems: usize,
@@ -82,7 +114,7 @@ mod test {
 
     impl<'i, L> LintTest<'i, L>
     where
-        L: Lint<'i> + 'static,
+        L: Lint<'i> + 'i,
     {
         pub fn run(self) {
             let id = self.lint.id();
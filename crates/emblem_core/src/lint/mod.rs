@@ -1,51 +1,79 @@
 mod lints;
 
+pub use lints::DashConvention;
+
 use crate::args::ArgPath;
 use crate::ast::parsed::{Content, Sugar};
 use crate::ast::{File, Par, ParPart};
 use crate::context::Context;
 use crate::log::messages::Message;
-use crate::parser;
+use crate::parser::{self, newlines};
 use crate::path::SearchResult;
 use crate::Action;
 use crate::Log;
 use crate::{context, EmblemResult};
 use derive_new::new;
+use std::ops::Range;
+use std::{fs, io};
 
 #[derive(new)]
 pub struct Linter {
     input: ArgPath,
-
-    #[allow(unused)]
     fix: bool,
+    dashes: Option<DashConvention>,
 }
 
 impl Action for Linter {
     type Response = ();
 
     fn run<'ctx>(&self, ctx: &'ctx mut context::Context) -> EmblemResult<'ctx, Self::Response> {
+        if self.fix {
+            if let Err(e) = self.fix() {
+                return EmblemResult::new(vec![Log::error(e.to_string())], ());
+            }
+        }
+
         let problems = match self.input.as_ref().try_into() {
             Ok(r) => self.lint_root(ctx, r),
             Err(e) => vec![Log::error(e.to_string())],
         };
-        EmblemResult::new(problems, ())
+        EmblemResult::new(ctx.diagnostic_params().apply(problems), ())
     }
 }
 
 impl Linter {
-    fn lint_root<'em>(&self, ctx: &'em mut Context, file: SearchResult) -> Vec<Log<'em>> {
-        let file = match parser::parse_file(ctx, file) {
-            Ok(f) => f,
+    /// Apply fixes to the raw source, before it is linted. Line endings are fixed first so that
+    /// the fixes suggested by lints are made to the source as it will be written.
+    fn fix(&self) -> io::Result<()> {
+        let ArgPath::Path(path) = &self.input else {
+            return Ok(());
+        };
+
+        // Invalid UTF-8 is reported when the file is parsed.
+        let Ok(raw) = String::from_utf8(fs::read(path)?) else {
+            return Ok(());
+        };
+
+        let newlines_fixed = newlines::fix(&raw);
+        let src = newlines_fixed.as_deref().unwrap_or(&raw);
+        match lints::fix(src, self.dashes).or(newlines_fixed) {
+            Some(fixed) => fs::write(path, fixed),
+            None => Ok(()),
+        }
+    }
+
+    fn lint_root<'em>(&self, ctx: &'em Context, file: SearchResult) -> Vec<Log<'em>> {
+        let (file, mut problems) = match parser::parse_file(ctx, file) {
+            Ok(parsed) => parsed,
             Err(e) => return vec![e.log()],
         };
 
-        let mut problems = Vec::new();
-        file.lint(&mut lints::lints(), &mut problems);
+        file.lint(&mut lints::lints(self.dashes), &mut problems);
         problems
     }
 }
 
-pub type Lints<'i> = Vec<Box<dyn Lint<'i>>>;
+pub type Lints<'i> = Vec<Box<dyn Lint<'i> + 'i>>;
 
 pub trait Lint<'i> {
     fn analyse(&mut self, content: &Content<'i>) -> Vec<Log<'i>>;
@@ -54,9 +82,21 @@ pub trait Lint<'i> {
         vec![]
     }
 
+    /// Take the fixes for the problems found so far.
+    fn fixes(&mut self) -> Vec<Fix> {
+        vec![]
+    }
+
     fn id(&self) -> &'static str;
 }
 
+/// A replacement for part of a source file which resolves a problem found by a lint.
+#[derive(Clone, Debug, Eq, PartialEq, new)]
+pub struct Fix {
+    range: Range<usize>,
+    replacement: &'static str,
+}
+
 pub trait Lintable<'i> {
     fn lint(&self, lints: &mut Lints<'i>, problems: &mut Vec<Log<'i>>);
 }
@@ -151,3 +191,89 @@ impl<'i, T: Lintable<'i>> Lintable<'i> for Vec<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fix_line_endings() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("main.em");
+        fs::write(&path, "foo\r\nbar\nbaz\r\n")?;
+
+        {
+            let mut ctx = Context::test_new();
+            let result = Linter::new(ArgPath::Path(path.clone()), false, None).run(&mut ctx);
+            assert!(result
+                .logs
+                .iter()
+                .any(|log| log.msg() == "mixed line endings"));
+        }
+        assert_eq!("foo\r\nbar\nbaz\r\n", fs::read_to_string(&path)?);
+
+        {
+            let mut ctx = Context::test_new();
+            let result = Linter::new(ArgPath::Path(path.clone()), true, None).run(&mut ctx);
+            assert!(result
+                .logs
+                .iter()
+                .all(|log| log.msg() != "mixed line endings"));
+        }
+        assert_eq!("foo\r\nbar\r\nbaz\r\n", fs::read_to_string(&path)?);
+
+        Ok(())
+    }
+    #[test]
+    fn fix_dashes() -> io::Result<()> {
+        let tmpdir = tempfile::tempdir()?;
+        let path = tmpdir.path().join("main.em");
+        let src = "foo --- bar\r\nbaz---qux\nquux\t--  corge -- grault\r\n";
+        fn is_dash_problem(log: &Log) -> bool {
+            log.id() == Some("dash-consistency")
+        }
+
+        for (dashes, problems, fixed) in [
+            (
+                None,
+                3,
+                "foo --- bar\r\nbaz --- qux\r\nquux --- corge --- grault\r\n",
+            ),
+            (
+                Some(DashConvention::SpacedEn),
+                2,
+                "foo -- bar\r\nbaz -- qux\r\nquux\t--  corge -- grault\r\n",
+            ),
+            (
+                Some(DashConvention::UnspacedEm),
+                3,
+                "foo---bar\r\nbaz---qux\r\nquux---corge---grault\r\n",
+            ),
+        ] {
+            fs::write(&path, src)?;
+
+            {
+                let mut ctx = Context::test_new();
+                let result = Linter::new(ArgPath::Path(path.clone()), false, dashes).run(&mut ctx);
+                assert_eq!(
+                    problems,
+                    result
+                        .logs
+                        .iter()
+                        .filter(|log| is_dash_problem(log))
+                        .count()
+                );
+            }
+            assert_eq!(src, fs::read_to_string(&path)?);
+
+            {
+                let mut ctx = Context::test_new();
+                let result = Linter::new(ArgPath::Path(path.clone()), true, dashes).run(&mut ctx);
+                assert!(!result.logs.iter().any(is_dash_problem));
+            }
+            assert_eq!(fixed, fs::read_to_string(&path)?);
+        }
+
+        Ok(())
+    }
+}
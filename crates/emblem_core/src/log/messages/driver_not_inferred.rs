@@ -0,0 +1,37 @@
+use crate::drivers;
+use crate::log::messages::Message;
+use crate::log::Log;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct DriverNotInferred {
+    available: Vec<&'static str>,
+}
+
+impl<'i> Message<'i> for DriverNotInferred {
+    fn id() -> &'static str {
+        "E006"
+    }
+
+    fn log(self) -> Log<'i> {
+        Log::error("could not infer output format")
+            .with_id(Self::id())
+            .explainable()
+            .with_help("try specifying a driver with -T")
+            .with_note(drivers::describe(&self.available))
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that no output driver was given and Emblem could not work out which
+            to use from the name of the output file. Drivers are inferred from the output file's
+            extension, so this usually happens when writing to stdout or to a file with an
+            unfamiliar extension.
+
+            To avoid this error, name a driver explicitly, for example:
+            em build doc.em - -T gemtext
+            or name one under driver in the project's manifest.
+        "}
+    }
+}
@@ -0,0 +1,70 @@
+use crate::log::messages::Message;
+use crate::log::Log;
+use derive_new::new;
+use indoc::indoc;
+
+/// What was being done when the extension runtime failed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExtensionStage {
+    #[default]
+    Setup,
+    Typeset,
+}
+
+#[derive(Default, new)]
+pub struct ExtensionFailure {
+    stage: ExtensionStage,
+    error: String,
+}
+
+impl ExtensionFailure {
+    fn hint(&self) -> Option<&'static str> {
+        if self.error.contains("too much memory used") || self.error.contains("not enough memory") {
+            Some("try raising the memory limit with --max-mem")
+        } else if self.error.contains("too many steps") {
+            Some("try raising the step limit with --max-steps")
+        } else if self.error.contains("is not in the allowlist") {
+            Some("to allow access, list the variable under env in the manifest")
+        } else if self.error.contains("is outside the sandbox") {
+            Some("to allow reading, list it under read-only in the manifest")
+        } else {
+            None
+        }
+    }
+}
+
+impl<'i> Message<'i> for ExtensionFailure {
+    fn id() -> &'static str {
+        "E007"
+    }
+
+    fn log(self) -> Log<'i> {
+        let mut log = Log::error(match self.stage {
+            ExtensionStage::Setup => "failed to start the extension runtime",
+            ExtensionStage::Typeset => "extension runtime failed while typesetting",
+        })
+        .with_id(Self::id())
+        .explainable();
+        if let Some(hint) = self.hint() {
+            log = log.with_help(hint);
+        }
+        if !self.error.is_empty() {
+            log = log.with_note(self.error);
+        }
+        log
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that the Lua runtime which hosts extensions could not be started, or
+            stopped unexpectedly while the document was being typeset. The attached note contains
+            the underlying error.
+
+            Most commonly, this is caused by an extension exceeding its memory or step limits, or
+            by it trying to access something which the sandbox does not allow, such as an
+            environment variable or a file outside the document's directory. Limits may be raised
+            with --max-mem and --max-steps, and the sandbox may be relaxed with --sandbox or by
+            listing what is needed in the manifest.
+        "}
+    }
+}
@@ -0,0 +1,66 @@
+use crate::log::messages::Message;
+use crate::log::Log;
+use derive_new::new;
+use indoc::indoc;
+
+/// When a failing build hook was run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HookStage {
+    #[default]
+    PreBuild,
+    PostBuild,
+}
+
+#[derive(Default, new)]
+pub struct HookFailure {
+    stage: HookStage,
+    command: String,
+    error: String,
+}
+
+impl HookFailure {
+    fn hint(&self) -> Option<&'static str> {
+        if self.error.contains("unavailable to the sandbox") {
+            Some("try running with --sandbox unrestricted")
+        } else {
+            None
+        }
+    }
+}
+
+impl<'i> Message<'i> for HookFailure {
+    fn id() -> &'static str {
+        "E008"
+    }
+
+    fn log(self) -> Log<'i> {
+        let mut log = Log::error(match self.stage {
+            HookStage::PreBuild => "pre-build hook failed",
+            HookStage::PostBuild => "post-build hook failed",
+        })
+        .with_id(Self::id())
+        .explainable();
+        if let Some(hint) = self.hint() {
+            log = log.with_help(hint);
+        }
+        match (self.command.is_empty(), self.error.is_empty()) {
+            (_, true) => {}
+            (true, false) => log = log.with_note(self.error),
+            (false, false) => log = log.with_note(format!("{}: {}", self.command, self.error)),
+        }
+        log
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that a command listed under the pre-build or post-build hooks in the
+            manifest could not be run, or exited unsuccessfully. A failing pre-build hook stops the
+            build before the document is parsed; a failing post-build hook is reported after the
+            output has been written.
+
+            Check that the command named in the note exists and works when run by hand from the
+            document's directory. Hooks are only run when the sandbox is disabled, so running
+            with --sandbox unrestricted may also be needed.
+        "}
+    }
+}
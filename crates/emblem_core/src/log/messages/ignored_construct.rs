@@ -0,0 +1,59 @@
+use crate::log::messages::Message;
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct IgnoredConstruct<'i> {
+    loc: Location<'i>,
+    construct: Construct,
+}
+
+/// Categories of source construct which may be dropped during typesetting.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Construct {
+    #[default]
+    SpiltGlue,
+}
+
+impl Construct {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SpiltGlue => "spilt glue",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        match self {
+            Self::SpiltGlue => "glue must directly touch the text either side",
+        }
+    }
+}
+
+impl<'i> Message<'i> for IgnoredConstruct<'i> {
+    fn id() -> &'static str {
+        "E009"
+    }
+
+    fn log(self) -> Log<'i> {
+        Log::warn(format!("ignored {}", self.construct.name()))
+            .with_id(Self::id())
+            .explainable()
+            .with_src(Src::new(&self.loc).with_annotation(Note::warn(&self.loc, "found here")))
+            .with_help(self.construct.help())
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This warning means that part of the document was read but had no effect on the
+            output, so it was dropped during typesetting.
+
+            For example, glue (~) separated from the words either side by whitespace cannot join
+            anything together, so it is ignored:
+            hello ~ world
+            To join the words, write the glue directly between them:
+            hello~world
+        "}
+    }
+}
@@ -0,0 +1,57 @@
+use crate::log::messages::Message;
+use crate::log::Log;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct InvalidDriverOption {
+    driver: &'static str,
+    option: String,
+
+    /// A description of the values the option accepts, if the option exists.
+    expected: Option<String>,
+
+    /// The options the driver accepts, if the option does not exist.
+    known: Vec<&'static str>,
+}
+
+impl<'i> Message<'i> for InvalidDriverOption {
+    fn id() -> &'static str {
+        "E015"
+    }
+
+    fn log(self) -> Log<'i> {
+        if let Some(expected) = self.expected {
+            return Log::error(format!("invalid value for option ‘{}’", self.option))
+                .with_id(Self::id())
+                .explainable()
+                .with_note(format!("expected {expected}"));
+        }
+
+        let note = if self.known.is_empty() {
+            format!("the {} driver takes no options", self.driver)
+        } else {
+            format!("known options: {}", self.known.join(", "))
+        };
+        Log::error(format!(
+            "unknown option ‘{}’ for driver ‘{}’",
+            self.option, self.driver
+        ))
+        .with_id(Self::id())
+        .explainable()
+        .with_note(note)
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that an option passed to the output driver, either with --driver-opt
+            or under drivers in the manifest, was not one which the driver accepts, or was given a
+            value of the wrong kind. Each driver declares the options it understands and the values
+            each may take, for example true or false.
+
+            The attached note lists the options the driver knows or the values it expected. Check
+            the spelling of the option and that it is meant for the driver being used, as options
+            given with --driver-opt apply to whichever driver is selected.
+        "}
+    }
+}
@@ -0,0 +1,51 @@
+use crate::log::messages::Message;
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct InvalidUtf8<'i> {
+    loc: Location<'i>,
+    offset: usize,
+    lossy: bool,
+}
+
+impl<'i> Message<'i> for InvalidUtf8<'i> {
+    fn id() -> &'static str {
+        "E010"
+    }
+
+    fn log(self) -> Log<'i> {
+        if self.lossy {
+            return Log::warn("replaced invalid UTF-8 in input")
+                .with_id(Self::id())
+                .explainable()
+                .with_src(Src::new(&self.loc).with_annotation(Note::warn(
+                    &self.loc,
+                    format!("replaced bytes from offset {}", self.offset),
+                )));
+        }
+
+        Log::error("input is not valid UTF-8")
+            .with_id(Self::id())
+            .explainable()
+            .with_src(Src::new(&self.loc).with_annotation(Note::error(
+                &self.loc,
+                format!("invalid bytes from offset {}", self.offset),
+            )))
+            .with_help("try re-encoding as UTF-8 or passing --lossy-input")
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that an input file contained bytes which are not valid UTF-8, the
+            only encoding Emblem accepts. This often happens when a file was saved by an editor
+            using a legacy encoding such as Latin-1 or Windows-1252.
+
+            To fix this, re-encode the file as UTF-8, for example with iconv. Alternatively, pass
+            --lossy-input to replace any invalid bytes with the Unicode replacement character and
+            continue with a warning.
+        "}
+    }
+}
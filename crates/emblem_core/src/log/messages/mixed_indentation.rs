@@ -0,0 +1,50 @@
+use crate::log::messages::Message;
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct MixedIndentation<'i> {
+    loc: Location<'i>,
+    first_loc: Option<Location<'i>>,
+    in_standoff: bool,
+}
+
+impl<'i> Message<'i> for MixedIndentation<'i> {
+    fn id() -> &'static str {
+        "E011"
+    }
+
+    fn log(self) -> Log<'i> {
+        let msg = if self.in_standoff {
+            "mixed tabs and spaces in heading standoff"
+        } else {
+            "mixed tabs and spaces in indentation"
+        };
+
+        let mut log = Log::warn(msg)
+            .with_id(Self::id())
+            .explainable()
+            .with_src(Src::new(&self.loc).with_annotation(Note::warn(&self.loc, "found here")));
+        if let Some(first_loc) = &self.first_loc {
+            log = log.with_src(
+                Src::new(first_loc)
+                    .with_annotation(Note::info(first_loc, "indentation style set here")),
+            );
+        }
+        log.with_help("use either tabs or spaces consistently")
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This warning means that a line was indented with a different mix of tabs and spaces
+            from earlier lines. As Emblem uses indentation to decide which lines belong to a
+            trailer argument, mixing the two can make a document's structure differ from how it
+            looks in an editor.
+
+            To avoid this warning, choose either tabs or spaces and use them consistently
+            throughout each file.
+        "}
+    }
+}
@@ -0,0 +1,42 @@
+use crate::log::messages::Message;
+use crate::log::{Log, Note, Src};
+use crate::parser::{LineEnding, Location};
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct MixedLineEndings<'i> {
+    loc: Location<'i>,
+    found: LineEnding,
+    expected: LineEnding,
+}
+
+impl<'i> Message<'i> for MixedLineEndings<'i> {
+    fn id() -> &'static str {
+        "E012"
+    }
+
+    fn log(self) -> Log<'i> {
+        Log::warn("mixed line endings")
+            .with_id(Self::id())
+            .explainable()
+            .with_src(Src::new(&self.loc).with_annotation(Note::warn(
+                &self.loc,
+                format!("line ends with {}, expected {}", self.found, self.expected),
+            )))
+            .with_help("try running ‘em lint --fix’")
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This warning means that a file used more than one style of line ending, for example
+            Unix-style LF on some lines and Windows-style CRLF on others. Emblem normalises line
+            endings before parsing, so the document is still read correctly, but mixed endings
+            often lead to noisy diffs.
+
+            To fix this, configure your editor to use one style of line ending, or run
+            em lint --fix
+            to rewrite the file using whichever style it uses most.
+        "}
+    }
+}
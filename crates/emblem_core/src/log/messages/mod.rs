@@ -1,11 +1,21 @@
 mod delimiter_mismatch;
+mod driver_not_inferred;
 mod empty_qualifier;
+mod extension_failure;
 mod extra_comment_close;
 mod heading_too_deep;
+mod hook_failure;
+mod ignored_construct;
+mod invalid_driver_option;
+mod invalid_utf8;
+mod mixed_indentation;
+mod mixed_line_endings;
 mod newline_in_attrs;
 mod newline_in_emph_delimiter;
 mod newline_in_inline_arg;
+mod no_such_driver;
 mod no_such_error_code;
+mod spilt_glue_repair;
 mod too_many_qualifiers;
 mod unclosed_comments;
 mod unexpected_char;
@@ -14,13 +24,23 @@ mod unexpected_heading;
 mod unexpected_token;
 
 pub use delimiter_mismatch::DelimiterMismatch;
+pub use driver_not_inferred::DriverNotInferred;
 pub use empty_qualifier::EmptyQualifier;
+pub use extension_failure::{ExtensionFailure, ExtensionStage};
 pub use extra_comment_close::ExtraCommentClose;
 pub use heading_too_deep::HeadingTooDeep;
+pub use hook_failure::{HookFailure, HookStage};
+pub use ignored_construct::{Construct, IgnoredConstruct};
+pub use invalid_driver_option::InvalidDriverOption;
+pub use invalid_utf8::InvalidUtf8;
+pub use mixed_indentation::MixedIndentation;
+pub use mixed_line_endings::MixedLineEndings;
 pub use newline_in_attrs::NewlineInAttrs;
 pub use newline_in_emph_delimiter::NewlineInEmphDelimiter;
 pub use newline_in_inline_arg::NewlineInInlineArg;
+pub use no_such_driver::NoSuchDriver;
 pub use no_such_error_code::NoSuchErrorCode;
+pub use spilt_glue_repair::SpiltGlueRepair;
 pub use too_many_qualifiers::TooManyQualifiers;
 pub use unclosed_comments::UnclosedComments;
 pub use unexpected_char::UnexpectedChar;
@@ -98,13 +118,23 @@ pub fn messages() -> Vec<MessageInfo> {
 
     messages![
         DelimiterMismatch,
+        DriverNotInferred,
         EmptyQualifier,
+        ExtensionFailure,
         ExtraCommentClose,
         HeadingTooDeep,
+        HookFailure,
+        IgnoredConstruct,
+        InvalidDriverOption,
+        InvalidUtf8,
+        MixedIndentation,
+        MixedLineEndings,
         NewlineInAttrs,
         NewlineInEmphDelimiter,
         NewlineInInlineArg,
+        NoSuchDriver,
         NoSuchErrorCode,
+        SpiltGlueRepair,
         TooManyQualifiers,
         UnclosedComments,
         UnexpectedChar,
@@ -0,0 +1,35 @@
+use crate::drivers;
+use crate::log::messages::Message;
+use crate::log::Log;
+use derive_new::new;
+use indoc::indoc;
+
+#[derive(Default, new)]
+pub struct NoSuchDriver {
+    name: String,
+    available: Vec<&'static str>,
+}
+
+impl<'i> Message<'i> for NoSuchDriver {
+    fn id() -> &'static str {
+        "E013"
+    }
+
+    fn log(self) -> Log<'i> {
+        Log::error(format!("no such output driver ‘{}’", self.name))
+            .with_id(Self::id())
+            .explainable()
+            .with_note(drivers::describe(&self.available))
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This error means that an output driver was requested by name, but no driver with that
+            name exists. Driver names are case-sensitive.
+
+            The attached note lists the drivers which are available. Check the spelling of the
+            name passed to -T or given in the manifest, and that the driver is supported by this
+            version of Emblem.
+        "}
+    }
+}
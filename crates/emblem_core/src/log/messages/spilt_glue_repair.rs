@@ -0,0 +1,50 @@
+use crate::log::messages::Message;
+use crate::log::{Log, Note, Src};
+use crate::parser::Location;
+use derive_new::new;
+use indoc::indoc;
+
+/// Glue which is separated from the text either side by whitespace. Outside of strict mode, such
+/// glue is joined back onto the text where possible.
+#[derive(Default, new)]
+pub struct SpiltGlueRepair<'i> {
+    loc: Location<'i>,
+    glue: &'i str,
+    strict: bool,
+}
+
+impl<'i> Message<'i> for SpiltGlueRepair<'i> {
+    fn id() -> &'static str {
+        "E014"
+    }
+
+    fn log(self) -> Log<'i> {
+        if self.strict {
+            return Log::error("spilt glue is not allowed")
+                .with_id(Self::id())
+                .explainable()
+                .with_src(Src::new(&self.loc).with_annotation(Note::error(&self.loc, "found here")))
+                .with_help("glue must directly touch the text either side");
+        }
+
+        Log::warn("repaired spilt glue")
+            .with_id(Self::id())
+            .explainable()
+            .with_src(Src::new(&self.loc).with_annotation(Note::warn(&self.loc, "found here")))
+            .with_help(format!("remove the surrounding space: ‘{}’", self.glue))
+    }
+
+    fn explain(&self) -> &'static str {
+        indoc! {"
+            This message means that glue (~) was separated from the text either side by
+            whitespace, for example:
+            hello ~ world
+            Glue is meant to join its neighbours, so Emblem removes the surrounding space and
+            continues with a warning, as though the following had been written:
+            hello~world
+
+            In strict mode, this is reported as an error instead, and nothing is repaired. To
+            avoid this message, write glue directly between the words it joins.
+        "}
+    }
+}
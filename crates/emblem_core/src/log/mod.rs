@@ -276,6 +276,11 @@ impl<'i> Log<'i> {
         self.msg_type
     }
 
+    pub(crate) fn with_msg_type(mut self, msg_type: AnnotationType) -> Self {
+        self.msg_type = msg_type;
+        self
+    }
+
     pub fn with_id(mut self, id: &'static str) -> Self {
         self.id = Some(id);
         self
@@ -0,0 +1,103 @@
+use crate::parser::{Location, Point};
+use crate::FileName;
+use std::str;
+
+/// The position of a run of invalid UTF-8 which has been replaced during decoding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Replacement {
+    /// Byte offset of the invalid sequence in the raw input.
+    pub offset: usize,
+
+    /// Index of the replacement character in the decoded text.
+    pub index: usize,
+}
+
+impl Replacement {
+    pub fn location<'i>(&self, file_name: FileName, src: &'i str) -> Location<'i> {
+        let start = Point::new(file_name, src).shift(&src[..self.index]);
+        let end = start
+            .clone()
+            .shift(&src[self.index..self.index + char::REPLACEMENT_CHARACTER.len_utf8()]);
+        Location::new(&start, &end)
+    }
+}
+
+/// Decode raw input as UTF-8, substituting a replacement character for each invalid sequence.
+pub(crate) fn decode(raw: Vec<u8>) -> (String, Vec<Replacement>) {
+    let raw = match String::from_utf8(raw) {
+        Ok(decoded) => return (decoded, vec![]),
+        Err(e) => e.into_bytes(),
+    };
+
+    let mut decoded = String::with_capacity(raw.len());
+    let mut replacements = Vec::new();
+    let mut offset = 0;
+    let mut rest = &raw[..];
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                decoded.push_str(valid);
+                return (decoded, replacements);
+            }
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                decoded.push_str(
+                    str::from_utf8(valid).expect("internal error: valid prefix is not UTF-8"),
+                );
+
+                replacements.push(Replacement {
+                    offset: offset + valid.len(),
+                    index: decoded.len(),
+                });
+                decoded.push(char::REPLACEMENT_CHARACTER);
+
+                let invalid_len = e.error_len().unwrap_or(invalid.len());
+                offset += valid.len() + invalid_len;
+                rest = &invalid[invalid_len..];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let (decoded, replacements) = decode("hello, wörld!".into());
+        assert_eq!("hello, wörld!", decoded);
+        assert!(replacements.is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        let raw = b"foo\xffbar\n\xc3\x28baz\xe2\x82".to_vec();
+        let (decoded, replacements) = decode(raw);
+        assert_eq!("foo\u{fffd}bar\n\u{fffd}(baz\u{fffd}", decoded);
+        assert_eq!(
+            vec![
+                Replacement {
+                    offset: 3,
+                    index: 3
+                },
+                Replacement {
+                    offset: 8,
+                    index: 10
+                },
+                Replacement {
+                    offset: 13,
+                    index: 17
+                },
+            ],
+            replacements
+        );
+    }
+
+    #[test]
+    fn location() {
+        let (decoded, replacements) = decode(b"foo\nbar\xffbaz".to_vec());
+        let loc = replacements[0].location(FileName::new("main.em"), &decoded);
+        assert_eq!("main.em:2:4-4", loc.to_string());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     log::{
-        messages::{UnexpectedEOF, UnexpectedToken},
-        Log, Message,
+        messages::{InvalidUtf8, UnexpectedEOF, UnexpectedToken},
+        Log, Message, Note, Src,
     },
     parser::{
         self,
@@ -21,6 +21,7 @@ pub type LalrpopError<'i> = LalrpopParseError<Point<'i>, Tok<'i>, Box<LexicalErr
 pub enum Error<'i> {
     StringConversion(StringConversionError),
     Filesystem(io::Error),
+    Encoding { loc: Location<'i>, offset: usize },
     Parse(LalrpopError<'i>),
 }
 
@@ -29,9 +30,12 @@ impl<'i> Message<'i> for parser::Error<'i> {
         match self {
             parser::Error::StringConversion(e) => Log::error(e.to_string()),
             parser::Error::Filesystem(e) => Log::error(e.to_string()),
+            parser::Error::Encoding { loc, offset } => InvalidUtf8::new(loc, offset, false).log(),
             parser::Error::Parse(e) => match e {
                 LalrpopError::InvalidToken { location } => {
-                    panic!("internal error: invalid token at {}", location)
+                    let loc = Location::new(&location, &location);
+                    Log::error("invalid token")
+                        .with_src(Src::new(&loc).with_annotation(Note::error(&loc, "found here")))
                 }
                 LalrpopError::UnrecognizedEOF { location, expected } => {
                     UnexpectedEOF::new(location, expected).log()
@@ -40,11 +44,12 @@ impl<'i> Message<'i> for parser::Error<'i> {
                     token: (l, t, r),
                     expected,
                 } => UnexpectedToken::new(Location::new(&l, &r), t, expected).log(),
-                LalrpopError::ExtraToken { token: (l, t, r) } => panic!(
-                    "internal error: extra token {} at {}",
-                    t,
-                    Location::new(&l, &r)
-                ),
+                LalrpopError::ExtraToken { token: (l, t, r) } => {
+                    let loc = Location::new(&l, &r);
+                    Log::error("unexpected token after end of input").with_src(
+                        Src::new(&loc).with_annotation(Note::error(&loc, format!("found {t}"))),
+                    )
+                }
                 LalrpopError::User { error } => error.log(),
             },
         }
@@ -58,6 +63,9 @@ impl Display for Error<'_> {
         match self {
             Self::StringConversion(e) => e.fmt(f),
             Self::Filesystem(e) => e.fmt(f),
+            Self::Encoding { loc, offset } => {
+                write!(f, "{loc}: invalid UTF-8 from byte offset {offset}")
+            }
             Self::Parse(e) => e.fmt(f),
         }
     }
@@ -1,7 +1,8 @@
+use crate::context::DEFAULT_TAB_WIDTH;
 use crate::log::messages::{
-    DelimiterMismatch, EmptyQualifier, ExtraCommentClose, HeadingTooDeep, NewlineInAttrs,
-    NewlineInEmphDelimiter, NewlineInInlineArg, TooManyQualifiers, UnclosedComments,
-    UnexpectedChar, UnexpectedEOF, UnexpectedHeading,
+    DelimiterMismatch, EmptyQualifier, ExtraCommentClose, HeadingTooDeep, MixedIndentation,
+    NewlineInAttrs, NewlineInEmphDelimiter, NewlineInInlineArg, TooManyQualifiers,
+    UnclosedComments, UnexpectedChar, UnexpectedEOF, UnexpectedHeading,
 };
 use crate::log::Log;
 use crate::parser::Location;
@@ -14,6 +15,7 @@ use std::{
     collections::VecDeque,
     error::Error,
     fmt::{self, Display},
+    num::NonZeroUsize,
 };
 
 pub struct Lexer<'input> {
@@ -31,6 +33,9 @@ pub struct Lexer<'input> {
     attr_open: Option<Location<'input>>,
     opening_delimiters: bool,
     open_delimiters: Vec<(&'input str, Location<'input>)>,
+    tab_width: NonZeroUsize,
+    indent_style: Option<(char, Location<'input>)>,
+    problems: Vec<Log<'input>>,
 }
 
 impl<'input> Lexer<'input> {
@@ -50,9 +55,25 @@ impl<'input> Lexer<'input> {
             attr_open: None,
             opening_delimiters: true,
             open_delimiters: Vec::new(),
+            tab_width: DEFAULT_TAB_WIDTH,
+            indent_style: None,
+            problems: Vec::new(),
         }
     }
 
+    /// Set the number of columns between tab stops.
+    pub fn with_tab_width(mut self, tab_width: NonZeroUsize) -> Self {
+        self.tab_width = tab_width;
+        self.curr_point = self.curr_point.with_tab_width(tab_width);
+        self.prev_point = self.prev_point.with_tab_width(tab_width);
+        self
+    }
+
+    /// Take the non-fatal problems found so far.
+    pub fn take_problems(&mut self) -> Vec<Log<'input>> {
+        std::mem::take(&mut self.problems)
+    }
+
     fn try_consume(&mut self, re: &Regex) -> Option<&'input str> {
         if let Some(mat) = re.find(self.input) {
             self.input = &self.input[mat.end()..];
@@ -110,6 +131,31 @@ impl<'input> Lexer<'input> {
         Location::new(&self.prev_point, &self.curr_point)
     }
 
+    /// Check that the indentation just consumed does not mix tabs and spaces, either within
+    /// itself or with the indentation used earlier in the file.
+    fn check_indentation(&mut self, indent: &str) {
+        let loc = self.location();
+        let style = match (indent.contains('\t'), indent.contains(' ')) {
+            (true, true) => {
+                self.problems
+                    .push(MixedIndentation::new(loc, None, false).log());
+                return;
+            }
+            (true, false) => '\t',
+            _ => ' ',
+        };
+
+        match &self.indent_style {
+            None => self.indent_style = Some((style, loc)),
+            Some((first_style, first_loc)) if *first_style != style => {
+                let first_loc = first_loc.clone();
+                self.problems
+                    .push(MixedIndentation::new(loc, Some(first_loc), false).log());
+            }
+            Some(_) => {}
+        }
+    }
+
     fn emph(&mut self, raw: &'input str) -> Result<Tok<'input>, Box<LexicalError<'input>>> {
         if self.opening_delimiters {
             self.open_delimiters.push((raw, self.location()));
@@ -120,7 +166,7 @@ impl<'input> Lexer<'input> {
                 "=" => Ok(Tok::SmallcapsOpen(raw)),
                 "==" => Ok(Tok::AlternateFaceOpen(raw)),
                 "`" => Ok(Tok::MonospaceOpen(raw)),
-                _ => panic!("internal error: unknown emphasis string {:?}", raw),
+                _ => Err(self.unexpected(raw)),
             };
         }
 
@@ -142,61 +188,71 @@ impl<'input> Lexer<'input> {
             "=" => Ok(Tok::SmallcapsClose),
             "==" => Ok(Tok::AlternateFaceClose),
             "`" => Ok(Tok::MonospaceClose),
-            _ => panic!("internal error: unknown emphasis string {:?}", raw),
+            _ => Err(self.unexpected(raw)),
         }
     }
+
+    /// Report the start of some just-consumed text which could not be turned into a token. This
+    /// stops lexing.
+    fn unexpected(&mut self, raw: &str) -> Box<LexicalError<'input>> {
+        self.failed = true;
+        Box::new(LexicalError::UnexpectedChar {
+            loc: self.location(),
+            found: raw.chars().next().unwrap_or_default(),
+        })
+    }
+}
+
+macro_rules! token_patterns {
+    ( $(let $name:ident = $pattern:literal);* $(;)? ) => {
+        lazy_static! {
+            $(static ref $name: Regex = Regex::new(concat!("^", $pattern)).unwrap();)*
+        }
+    }
+}
+
+token_patterns! {
+    let SHEBANG = r"#![^\r\n]*";
+
+    let WORD           = r"([^ /\t\r\n}_*`=~-]|/[^ /\t\r\n}_*`=~-])+";
+    let WHITESPACE     = r"[ \t]+";
+    let PAR_BREAKS     = r"([ \t]*(\n|\r\n|\r))+";
+    let LN             = r"(\n|\r\n|\r)";
+    let COLON          = r":[ \t]*";
+    let DOUBLE_COLON   = r"::";
+    let INITIAL_INDENT = r"[ \t]*";
+    let VERBATIM       = r"![^!\r\n]+!";
+    let BRACE_LEFT     = r"\{";
+    let BRACE_RIGHT    = r"\}";
+    let COMMENT        = r"//[^\r\n]*";
+    let DASH           = r"-{1,3}";
+    let GLUE           = r" *~~? *";
+    let UNDERSCORES    = r"_{1,2}";
+    let ASTERISKS      = r"\*{1,2}";
+    let EQUALS         = r"={1,2}";
+    let BACKTICKS      = r"`";
+    let HEADING        = r"#+\+*";
+    let MARK           = r#"@[^ \t\r\n#+.,?!'"(){}\[\]]+"#;
+    let REFERENCE      = r#"#[^ \t\r\n#+.,?!'"(){}\[\]]+"#;
+
+    let QUALIFIED_COMMAND = r"(\.+[^ \t{}\[\]\r\n:+.]*){2,}[^ \t{}\[\]\r\n:+.]\+*";
+    let COMMAND           = r"\.[^ \t{}\[\]\r\n:+.]+\+*";
+
+    let OPEN_ATTRS   = r"\[";
+    let CLOSE_ATTRS  = r"]";
+    let COMMA        = r",";
+    let UNNAMED_ATTR = r"[ \t]*([^,= \r\n\t\[\]]|\\[,=\[\]])+[ \t]*";
+    let NAMED_ATTR   = r"[ \t]*([^,= \r\n\t\[\]]|\\[,=\[\]])+[ \t]*=[ \t]*([^,\[\]\r\n]|\\[,\[\]])*[ \t]*";
+
+    let NESTED_COMMENT_OPEN  = r"/\*";
+    let NESTED_COMMENT_CLOSE = r"\*/";
+    let NESTED_COMMENT_PART  = r"([^*/\r\n]|\*[^/\r\n]|/[^*\r\n])+";
 }
 
 impl<'input> Iterator for Lexer<'input> {
     type Item = Result<SpannedTok<'input>, Box<LexicalError<'input>>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        macro_rules! token_patterns {
-            ( $(let $name:ident = $pattern:literal);* $(;)? ) => {
-                lazy_static! {
-                    $(static ref $name: Regex = Regex::new(concat!("^", $pattern)).unwrap();)*
-                }
-            }
-        }
-
-        token_patterns! {
-            let SHEBANG = r"#![^\r\n]*";
-
-            let WORD           = r"([^ /\t\r\n}_*`=~-]|/[^ /\t\r\n}_*`=~-])+";
-            let WHITESPACE     = r"[ \t]+";
-            let PAR_BREAKS     = r"([ \t]*(\n|\r\n|\r))+";
-            let LN             = r"(\n|\r\n|\r)";
-            let COLON          = r":[ \t]*";
-            let DOUBLE_COLON   = r"::";
-            let INITIAL_INDENT = r"[ \t]*";
-            let VERBATIM       = r"![^!\r\n]+!";
-            let BRACE_LEFT     = r"\{";
-            let BRACE_RIGHT    = r"\}";
-            let COMMENT        = r"//[^\r\n]*";
-            let DASH           = r"-{1,3}";
-            let GLUE           = r" *~~? *";
-            let UNDERSCORES    = r"_{1,2}";
-            let ASTERISKS      = r"\*{1,2}";
-            let EQUALS         = r"={1,2}";
-            let BACKTICKS      = r"`";
-            let HEADING        = r"#+\+*";
-            let MARK           = r#"@[^ \t\r\n#+.,?!'"(){}\[\]]+"#;
-            let REFERENCE      = r#"#[^ \t\r\n#+.,?!'"(){}\[\]]+"#;
-
-            let QUALIFIED_COMMAND = r"(\.+[^ \t{}\[\]\r\n:+.]*){2,}[^ \t{}\[\]\r\n:+.]\+*";
-            let COMMAND           = r"\.[^ \t{}\[\]\r\n:+.]+\+*";
-
-            let OPEN_ATTRS   = r"\[";
-            let CLOSE_ATTRS  = r"]";
-            let COMMA        = r",";
-            let UNNAMED_ATTR = r"[ \t]*([^,= \r\n\t\[\]]|\\[,=\[\]])+[ \t]*";
-            let NAMED_ATTR   = r"[ \t]*([^,= \r\n\t\[\]]|\\[,=\[\]])+[ \t]*=[ \t]*([^,\[\]\r\n]|\\[,\[\]])*[ \t]*";
-
-            let NESTED_COMMENT_OPEN  = r"/\*";
-            let NESTED_COMMENT_CLOSE = r"\*/";
-            let NESTED_COMMENT_PART  = r"([^*/\r\n]|\*[^/\r\n]|/[^*\r\n])+";
-        }
-
         if self.failed {
             return None;
         }
@@ -332,7 +388,8 @@ impl<'input> Iterator for Lexer<'input> {
 
             {
                 let target = if let Some(indent) = self.try_consume(&WHITESPACE) {
-                    indent_level(indent)
+                    self.check_indentation(indent);
+                    indent_level(indent, self.tab_width)
                 } else {
                     0
                 };
@@ -374,7 +431,9 @@ impl<'input> Iterator for Lexer<'input> {
                 }
 
                 let pluses = heading.len() - level;
-                return Some(Ok(self.span(Tok::Heading { level, pluses })));
+                let tok = Tok::Heading { level, pluses };
+                self.last_tok = Some(tok.clone());
+                return Some(Ok(self.span(tok)));
             }
         }
 
@@ -386,8 +445,27 @@ impl<'input> Iterator for Lexer<'input> {
             return Some(Ok(self.span(Tok::LBracket)));
         }
 
+        // Words are scanned with a lookup table rather than the regex engine. Whitespace,
+        // delimiters and all other tokens are still matched below.
+        if let Some(len) = word_len(self.input.as_bytes()) {
+            let word = &self.input[..len];
+            self.input = &self.input[len..];
+            self.shift_locs(word);
+            self.opening_delimiters = false;
+
+            let ret = self.span(Tok::Word(word));
+            self.last_tok = Some(ret.1.clone());
+            return Some(Ok(ret));
+        }
+
         match_token! {
-            ! => panic!("internal error: unexpected EOF"),
+            ! => {
+                self.failed = true;
+                Err(Box::new(LexicalError::UnexpectedEOF {
+                    point: self.curr_point.clone(),
+                    expected: vec![],
+                }))
+            },
 
             COMMENT      => |s: &'input str| Ok(Tok::Comment(&s[2..])),
             DOUBLE_COLON => |_| Ok(Tok::DoubleColon),
@@ -484,6 +562,9 @@ impl<'input> Iterator for Lexer<'input> {
                 Ok(Tok::Word(s))
             },
             WHITESPACE => |s:&'input str| {
+                if matches!(self.last_tok, Some(Tok::Heading { .. })) && s.contains('\t') && s.contains(' ') {
+                    self.problems.push(MixedIndentation::new(self.location(), None, true).log());
+                }
                 self.opening_delimiters = true;
                 Ok(Tok::Whitespace(s))
             },
@@ -586,8 +667,9 @@ impl Display for Tok<'_> {
     }
 }
 
-/// Compute the level of indentation for the given string.
-fn indent_level(s: &str) -> u32 {
+/// Compute the level of indentation for the given string, where each tab is one level and each
+/// level of spaces is `tab_width` wide.
+fn indent_level(s: &str, tab_width: NonZeroUsize) -> u32 {
     let mut tabs = 0;
     let mut spaces = 0;
 
@@ -599,7 +681,42 @@ fn indent_level(s: &str) -> u32 {
         }
     }
 
-    tabs + (spaces as f32 / 4_f32).ceil() as u32
+    tabs + (spaces as f32 / tab_width.get() as f32).ceil() as u32
+}
+
+const fn byte_set(bytes: &[u8]) -> [bool; 256] {
+    let mut set = [false; 256];
+    let mut i = 0;
+    while i < bytes.len() {
+        set[bytes[i] as usize] = true;
+        i += 1;
+    }
+    set
+}
+
+/// Bytes which end a word. All are ASCII, so a word never ends part-way through a character.
+static WORD_BREAKS: [bool; 256] = byte_set(b" /\t\r\n}_*`=~-");
+
+/// Bytes which may start a token which takes precedence over a word.
+static NON_WORD_STARTS: [bool; 256] = byte_set(b" /\t\r\n}_*`=~-{:.#@!");
+
+/// Find the length of the word at the start of the input, as matched by the `WORD` pattern.
+/// Returns `None` if the input may start with some other token.
+fn word_len(input: &[u8]) -> Option<usize> {
+    if NON_WORD_STARTS[*input.first()? as usize] {
+        return None;
+    }
+
+    let mut len = 1;
+    loop {
+        match input.get(len) {
+            Some(b'/') if matches!(input.get(len + 1), Some(b) if !WORD_BREAKS[*b as usize]) => {
+                len += 2
+            }
+            Some(b) if !WORD_BREAKS[*b as usize] => len += 1,
+            _ => return Some(len),
+        }
+    }
 }
 
 pub type SpannedTok<'input> = (Point<'input>, Tok<'input>, Point<'input>);
@@ -770,9 +887,15 @@ mod test {
     use super::*;
 
     fn test_indent_str(expected: u32, s: &str) {
-        assert_eq!(expected, indent_level(s));
-        assert_eq!(expected, indent_level(&format!("{}foo", s)));
-        assert_eq!(expected, indent_level(&format!("{}foo{}", s, s)));
+        assert_eq!(expected, indent_level(s, DEFAULT_TAB_WIDTH));
+        assert_eq!(
+            expected,
+            indent_level(&format!("{}foo", s), DEFAULT_TAB_WIDTH)
+        );
+        assert_eq!(
+            expected,
+            indent_level(&format!("{}foo{}", s, s), DEFAULT_TAB_WIDTH)
+        );
     }
 
     #[test]
@@ -787,4 +910,113 @@ mod test {
         test_indent_str(2, "        ");
         test_indent_str(3, "    \t    ");
     }
+
+    #[test]
+    fn indent_level_tab_width() {
+        let width = |width| NonZeroUsize::new(width).unwrap();
+        assert_eq!(1, indent_level("  ", width(2)));
+        assert_eq!(2, indent_level("    ", width(2)));
+        assert_eq!(1, indent_level("        ", width(8)));
+        assert_eq!(2, indent_level("\t  ", width(2)));
+    }
+
+    #[test]
+    fn word_len_matches_pattern() {
+        const ALPHABET: &[&str] = &[
+            "a", "Z", "0", "ö", "→", " ", "/", "\t", "\r", "\n", "}", "_", "*", "`", "=", "~", "-",
+            "{", ":", ".", "#", "@", "!", ",", "[", "]",
+        ];
+
+        // A fixed xorshift generator, so that failures can be reproduced.
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+
+        for _ in 0..100_000 {
+            let len = random() % 12;
+            let input: String = (0..len)
+                .map(|_| ALPHABET[random() % ALPHABET.len()])
+                .collect();
+            let bytes = input.as_bytes();
+            match word_len(bytes) {
+                Some(len) => assert_eq!(
+                    Some(len),
+                    WORD.find(&input).map(|mat| mat.end()),
+                    "{input:?}"
+                ),
+                None => assert!(
+                    bytes.first().map_or(true, |b| NON_WORD_STARTS[*b as usize]),
+                    "{input:?}"
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn word_scanning() {
+        assert_eq!(None, word_len(b""));
+        for start in [
+            " ", "/", "\t", "\n", "}", "_", "*", "`", "=", "~", "-", "{", ":", ".", "#", "@", "!",
+        ] {
+            assert_eq!(
+                None,
+                word_len(format!("{start}foo").as_bytes()),
+                "{start:?}"
+            );
+        }
+
+        assert_eq!(Some(5), word_len(b"hello world"));
+        assert_eq!(Some(6), word_len(b"hello."));
+        assert_eq!(Some(7), word_len(b"and/or "));
+        assert_eq!(Some(3), word_len(b"and// comment"));
+        assert_eq!(Some(3), word_len(b"and/"));
+        assert_eq!(Some(4), word_len(b"foo{}"));
+        assert_eq!(Some("wörld".len()), word_len("wörld_".as_bytes()));
+        assert_eq!(Some("a/ö".len()), word_len("a/ö-".as_bytes()));
+    }
+
+    fn problems(src: &str) -> Vec<String> {
+        let mut lexer = Lexer::new(FileName::new("main.em"), src);
+        for tok in &mut lexer {
+            assert!(tok.is_ok(), "unexpected lexical error");
+        }
+        lexer
+            .take_problems()
+            .iter()
+            .map(|problem| problem.annotation_text().join("\n"))
+            .collect()
+    }
+
+    #[test]
+    fn mixed_indentation() {
+        assert!(problems(".foo:\n\tbar\n\tbaz").is_empty());
+        assert!(problems(".foo:\n    bar\n        baz").is_empty());
+
+        let mixed_line = problems(".foo:\n\t  bar");
+        assert_eq!(1, mixed_line.len());
+        assert!(mixed_line[0].starts_with("mixed tabs and spaces in indentation"));
+        assert!(mixed_line[0].contains("main.em:2:1-6: found here"));
+
+        let mixed_file = problems(".foo:\n\tbar\n.baz:\n    qux");
+        assert_eq!(1, mixed_file.len());
+        assert!(mixed_file[0].contains("main.em:4:1-4: found here"));
+        assert!(mixed_file[0].contains("main.em:2:1-4: indentation style set here"));
+
+        let standoff = problems("#\t heading");
+        assert_eq!(1, standoff.len());
+        assert!(standoff[0].starts_with("mixed tabs and spaces in heading standoff"));
+    }
+
+    #[test]
+    fn tab_width() {
+        let mut lexer = Lexer::new(FileName::new("main.em"), "\tfoo")
+            .with_tab_width(NonZeroUsize::new(2).unwrap());
+        let (_, tok, end) = lexer.next().unwrap().unwrap();
+        assert_eq!(Tok::Whitespace("\t"), tok);
+        assert_eq!(3, end.col);
+    }
 }
@@ -1,9 +1,11 @@
 use crate::{
+    context::DEFAULT_TAB_WIDTH,
     parser::{LocationContext, Point},
     FileName,
 };
 use core::fmt::{self, Display};
 use std::cmp;
+use std::num::NonZeroUsize;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Location<'i> {
@@ -12,6 +14,7 @@ pub struct Location<'i> {
     lines: (usize, usize),
     cols: (usize, usize),
     indices: (usize, usize),
+    tab_width: NonZeroUsize,
 }
 
 impl<'i> Location<'i> {
@@ -22,6 +25,7 @@ impl<'i> Location<'i> {
             lines: (start.line, end.line),
             indices: (start.index, end.index),
             cols: (start.col, cmp::max(1, end.col - 1)),
+            tab_width: start.tab_width,
         }
     }
 
@@ -54,6 +58,7 @@ impl<'i> Location<'i> {
             line: self.lines.0,
             col: self.cols.0,
             index: self.indices.0,
+            tab_width: self.tab_width,
         }
     }
 
@@ -64,6 +69,7 @@ impl<'i> Location<'i> {
             line: self.lines.1,
             col: self.cols.1,
             index: self.indices.1,
+            tab_width: self.tab_width,
         }
     }
 
@@ -90,6 +96,7 @@ impl<'i> Location<'i> {
                 cmp::min(self.cols.0, other.cols.0),
                 cmp::max(self.cols.1, other.cols.1),
             ),
+            tab_width: self.tab_width,
         }
     }
 
@@ -115,6 +122,7 @@ impl Default for Location<'_> {
             lines: Default::default(),
             cols: Default::default(),
             indices: Default::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
 }
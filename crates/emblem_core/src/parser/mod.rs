@@ -1,23 +1,31 @@
+mod decode;
 pub mod error;
 pub mod lexer;
 pub mod location;
 mod location_context;
+pub(crate) mod newlines;
 mod point;
 
 pub use error::Error;
 pub use lexer::LexicalError;
 pub use location::Location;
 pub use location_context::LocationContext;
+pub use newlines::LineEnding;
 pub use point::Point;
 
-use crate::context::Context;
+use crate::args::ArgPath;
+use crate::context::{Context, DEFAULT_TAB_WIDTH};
+use crate::log::messages::{InvalidUtf8, MixedLineEndings};
+use crate::log::Message;
 use crate::path::SearchResult;
-use crate::{ast, FileName};
+use crate::{ast, EmblemResult, FileName, Input, Log};
 use ast::parsed::ParsedFile;
+use decode::decode;
 use error::StringConversionError;
 use lalrpop_util::lalrpop_mod;
 use lexer::Lexer;
 use std::io::{BufReader, Read};
+use std::num::NonZeroUsize;
 
 lalrpop_mod!(
     #[allow(clippy::all)]
@@ -25,11 +33,12 @@ lalrpop_mod!(
     "/parser/parser.rs"
 );
 
-/// Parse an emblem source file at the given location.
+/// Parse an emblem source file at the given location. Any invalid UTF-8 is rejected unless lossy
+/// input is enabled, in which case it is replaced and a warning is returned for each occurrence.
 pub fn parse_file<'ctx, 'input>(
     ctx: &'ctx Context<'ctx>,
     mut to_parse: SearchResult,
-) -> Result<ParsedFile<'input>, Box<Error<'input>>>
+) -> Result<(ParsedFile<'input>, Vec<Log<'input>>), Box<Error<'input>>>
 where
     'ctx: 'input,
 {
@@ -46,28 +55,130 @@ where
         ctx.alloc_file_name(path)
     };
 
-    let content = {
+    let (content, replacements) = {
         let file = to_parse.file();
         let hint = file.len_hint();
 
         let mut reader = BufReader::new(file);
         let mut buf = hint
             .and_then(|len| usize::try_from(len).ok())
-            .map(String::with_capacity)
+            .map(Vec::with_capacity)
             .unwrap_or_default();
-        reader.read_to_string(&mut buf)?;
-        ctx.alloc_file(buf)
+        reader.read_to_end(&mut buf)?;
+
+        let (decoded, mut replacements) = decode(buf);
+
+        // Account for the characters removed by normalising line endings.
+        let mut crlfs = 0;
+        let mut prev_index = 0;
+        for replacement in &mut replacements {
+            crlfs += decoded[prev_index..replacement.index]
+                .matches("\r\n")
+                .count();
+            prev_index = replacement.index;
+            replacement.index -= crlfs;
+        }
+
+        (decoded, replacements)
     };
 
-    parse(file, content)
+    let normalised = newlines::normalise(content);
+    let content = ctx.alloc_file(normalised.text);
+    let tab_width = ctx.input_params().tab_width();
+
+    let mut problems = Vec::with_capacity(replacements.len());
+    for replacement in replacements {
+        let loc = replacement.location(file.clone(), content);
+        if !ctx.input_params().lossy() {
+            return Err(Box::new(Error::Encoding {
+                loc,
+                offset: replacement.offset,
+            }));
+        }
+        problems.push(InvalidUtf8::new(loc, replacement.offset, true).log());
+    }
+
+    let (parsed, parse_problems) =
+        parse_with_mismatch(file, content, normalised.mismatch, tab_width)?;
+    problems.extend(parse_problems);
+    Ok((parsed, problems))
+}
+
+/// Parse emblem source code from the given input, as with `parse_file`.
+pub fn parse_input<'ctx, 'input>(
+    ctx: &'ctx Context<'ctx>,
+    input: &Input,
+) -> Result<(ParsedFile<'input>, Vec<Log<'input>>), Box<Error<'input>>>
+where
+    'ctx: 'input,
+{
+    let path = match input {
+        Input::Stdin => ArgPath::Stdio,
+        Input::File(path) => ArgPath::Path(path.clone()),
+        Input::Source { name, content } => {
+            let normalised = newlines::normalise(content.clone());
+            return parse_with_mismatch(
+                ctx.alloc_file_name(name),
+                ctx.alloc_file(normalised.text),
+                normalised.mismatch,
+                ctx.input_params().tab_width(),
+            );
+        }
+    };
+    parse_file(ctx, SearchResult::try_from(&path)?)
 }
 
 /// Parse a given string of emblem source code.
 pub fn parse(name: FileName, content: &str) -> Result<ParsedFile<'_>, Box<Error<'_>>> {
-    let lexer = Lexer::new(name, content);
+    parse_with_tab_width(name, content, DEFAULT_TAB_WIDTH).map(|(file, _)| file)
+}
+
+/// Parse a given string of emblem source code in which each tab is `tab_width` columns wide,
+/// returning any warnings raised while lexing.
+pub fn parse_with_tab_width(
+    name: FileName,
+    content: &str,
+    tab_width: NonZeroUsize,
+) -> Result<(ParsedFile<'_>, Vec<Log<'_>>), Box<Error<'_>>> {
+    let mut lexer = Lexer::new(name, content).with_tab_width(tab_width);
     let parser = parser::FileParser::new();
 
-    Ok(parser.parse(lexer)?)
+    let file = parser.parse(&mut lexer)?;
+    Ok((file, lexer.take_problems()))
+}
+
+/// Parse source code, warning about the first line ending which did not match the rest, if any.
+fn parse_with_mismatch<'i>(
+    file: FileName,
+    content: &'i str,
+    mismatch: Option<newlines::Mismatch>,
+    tab_width: NonZeroUsize,
+) -> Result<(ParsedFile<'i>, Vec<Log<'i>>), Box<Error<'i>>> {
+    let mut problems = Vec::new();
+    if let Some(mismatch) = mismatch {
+        let loc = mismatch.location(file.clone(), content, tab_width);
+        problems.push(MixedLineEndings::new(loc, mismatch.found, mismatch.expected).log());
+    }
+
+    let (parsed, lexer_problems) = parse_with_tab_width(file, content, tab_width)?;
+    problems.extend(lexer_problems);
+    Ok((parsed, problems))
+}
+
+/// Parse a given string of emblem source code, reporting any problems as diagnostics rather than
+/// as an error. Line endings are left as they are, but mixed line endings are reported as when
+/// reading a file. No `Context` is required and this should not panic on any input; it is the
+/// entry point exercised by the fuzz targets.
+pub fn parse_str<'i>(name: &str, content: &'i str) -> EmblemResult<'i, Option<ParsedFile<'i>>> {
+    match parse_with_mismatch(
+        FileName::new(name),
+        content,
+        newlines::mismatch(content),
+        DEFAULT_TAB_WIDTH,
+    ) {
+        Ok((file, problems)) => EmblemResult::new(problems, Some(file)),
+        Err(err) => EmblemResult::new(vec![err.log()], None),
+    }
 }
 
 #[cfg(test)]
@@ -866,6 +977,22 @@ pub mod test {
             assert_structure("ignored in comment", "//!asdf!", "File[Par[[//!asdf!]]]");
         }
 
+        #[test]
+        fn whitespace() {
+            assert_structure("leading", "!  verb!", "File[Par[[!  verb!]]]");
+            assert_structure("trailing", "!verb  !", "File[Par[[!verb  !]]]");
+            assert_structure("repeated", "!a   b!", "File[Par[[!a   b!]]]");
+            assert_structure("tabs", "!\ta\tb\t!", r"File[Par[[!\ta\tb\t!]]]");
+            assert_structure("mixed", "! \t a \t !", r"File[Par[[! \t a \t !]]]");
+            assert_structure("only-space", "! !", "File[Par[[! !]]]");
+            assert_structure("only-tab", "!\t!", r"File[Par[[!\t!]]]");
+            assert_structure(
+                "end-of-line",
+                "!verb  !\nfoo",
+                "File[Par[[!verb  !]|[Word(foo)]]]",
+            );
+        }
+
         #[test]
         fn multiple() {
             assert_structure(
@@ -1365,4 +1492,187 @@ pub mod test {
             }
         }
     }
+
+    mod parse_str {
+        use super::*;
+
+        #[test]
+        fn ok() {
+            let result = parse_str("ok.em", "hello, world!");
+            assert!(result.logs.is_empty());
+            assert_eq!(
+                "File[Par[[Word(hello,)|< >|Word(world!)]]]",
+                result.response.unwrap().repr()
+            );
+        }
+
+        #[test]
+        fn err() {
+            let result = parse_str("err.em", "#######");
+            assert!(result.response.is_none());
+            assert_eq!(1, result.logs.len());
+            assert!(result.logs[0].msg().contains("heading"));
+        }
+
+        #[test]
+        fn line_endings() {
+            let result = parse_str("crlf.em", "hello,\r\nworld!\n");
+            assert_eq!(1, result.logs.len(), "{:?}", result.logs);
+            assert_eq!("mixed line endings", result.logs[0].msg());
+            assert!(result.logs[0]
+                .annotation_text()
+                .iter()
+                .any(|text| text.contains("crlf.em:2:1-6")));
+            assert_eq!(
+                "File[Par[[Word(hello,)]|[Word(world!)]]]",
+                result.response.unwrap().repr()
+            );
+        }
+
+        #[test]
+        fn never_panics() {
+            const FRAGMENTS: &[&str] = &[
+                "a", " ", "\t", "\n", "\r\n", "_", "*", "__", "**", "=", "==", "`", "#", "#+", "+",
+                ".a", "{", "}", "[", "]", ":", "::", "~", "!", "//", "/*", "*/", "@m", "\\",
+            ];
+
+            fn check(src: &str, depth: usize) {
+                let result = parse_str("fuzz.em", src);
+                assert!(
+                    result.response.is_some() || !result.logs.is_empty(),
+                    "no result or problems for {src:?}"
+                );
+
+                if depth > 0 {
+                    for fragment in FRAGMENTS {
+                        check(&format!("{src}{fragment}"), depth - 1);
+                    }
+                }
+            }
+
+            check("", 3);
+        }
+    }
+
+    mod parse_input {
+        use super::*;
+        use std::{fs, io};
+
+        #[test]
+        fn source() {
+            let ctx = Context::test_new();
+            let input = Input::source("mem.em", "hello, world!");
+            let (file, problems) = parse_input(&ctx, &input).unwrap();
+            assert!(problems.is_empty());
+            assert_eq!("File[Par[[Word(hello,)|< >|Word(world!)]]]", file.repr());
+        }
+
+        #[test]
+        fn source_line_endings() {
+            let ctx = Context::test_new();
+
+            let input = Input::source("crlf.em", "hello,\r\nworld!\r\n");
+            let (file, problems) = parse_input(&ctx, &input).unwrap();
+            assert!(problems.is_empty(), "{problems:?}");
+            assert_eq!("File[Par[[Word(hello,)]|[Word(world!)]]]", file.repr());
+
+            let input = Input::source("mixed.em", "hello,\r\nworld!\nfoo\r\n");
+            let (file, problems) = parse_input(&ctx, &input).unwrap();
+            assert_eq!(
+                "File[Par[[Word(hello,)]|[Word(world!)]|[Word(foo)]]]",
+                file.repr()
+            );
+            assert_eq!(1, problems.len(), "{problems:?}");
+            let text = problems[0].annotation_text().join("\n");
+            assert!(
+                text.contains(":2:1-6: line ends with LF, expected CRLF"),
+                "{text}"
+            );
+        }
+
+        #[test]
+        fn file() -> io::Result<()> {
+            let tmpdir = tempfile::tempdir()?;
+            let path = tmpdir.path().join("main.em");
+            fs::write(&path, "hello, world!")?;
+
+            let ctx = Context::test_new();
+            let (file, problems) = parse_input(&ctx, &Input::File(path)).unwrap();
+            assert!(problems.is_empty());
+            assert_eq!("File[Par[[Word(hello,)|< >|Word(world!)]]]", file.repr());
+
+            Ok(())
+        }
+
+        #[test]
+        fn invalid_utf8() -> io::Result<()> {
+            let tmpdir = tempfile::tempdir()?;
+            let path = tmpdir.path().join("main.em");
+            fs::write(&path, b"hello,\n\xffworld!\xfe")?;
+            let input = Input::File(path);
+
+            {
+                let ctx = Context::test_new();
+                let err = parse_input(&ctx, &input).unwrap_err();
+                let log = err.log();
+                assert_eq!("input is not valid UTF-8", log.msg());
+                let text = log.annotation_text().join("\n");
+                assert!(
+                    text.contains(":2:1-1: invalid bytes from offset 7"),
+                    "{text}"
+                );
+            }
+
+            {
+                let mut ctx = Context::test_new();
+                ctx.input_params_mut().set_lossy(true);
+                let (_, problems) = parse_input(&ctx, &input).unwrap();
+                assert_eq!(2, problems.len());
+                assert_eq!("replaced invalid UTF-8 in input", problems[0].msg());
+                let text = problems[1].annotation_text().join("\n");
+                assert!(
+                    text.contains(":2:8-8: replaced bytes from offset 14"),
+                    "{text}"
+                );
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn line_endings() -> io::Result<()> {
+            let tmpdir = tempfile::tempdir()?;
+            let path = tmpdir.path().join("main.em");
+            fs::write(&path, b"hello,\r\nworld!\r\n\r\nfoo\nbar\xff")?;
+            let input = Input::File(path);
+
+            let mut ctx = Context::test_new();
+            ctx.input_params_mut().set_lossy(true);
+            let (file, problems) = parse_input(&ctx, &input).unwrap();
+            assert_eq!(
+                "File[Par[[Word(hello,)]|[Word(world!)]]|Par[[Word(foo)]|[Word(bar\u{fffd})]]]",
+                file.repr()
+            );
+            assert_eq!(2, problems.len());
+            let text = problems[0].annotation_text().join("\n");
+            assert!(
+                text.contains(":5:4-4: replaced bytes from offset 25"),
+                "{text}"
+            );
+            let text = problems[1].annotation_text().join("\n");
+            assert!(
+                text.contains(":4:1-3: line ends with LF, expected CRLF"),
+                "{text}"
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn missing_file() {
+            let ctx = Context::test_new();
+            let input = Input::File("non-existent.em".into());
+            assert!(parse_input(&ctx, &input).is_err());
+        }
+    }
 }
@@ -0,0 +1,258 @@
+use crate::parser::{Location, Point};
+use crate::FileName;
+use std::fmt::{self, Display};
+use std::num::NonZeroUsize;
+
+/// A style of line ending.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+            Self::Cr => "CR",
+        }
+        .fmt(f)
+    }
+}
+
+/// Text whose line endings have all been replaced with `\n`.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Normalised {
+    pub text: String,
+
+    /// The first line ending in the text which differs from the one before it, along with the
+    /// index of its replacement in the normalised text and the style which was expected.
+    pub mismatch: Option<Mismatch>,
+}
+
+/// A line ending which differs from the one before it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Mismatch {
+    pub index: usize,
+    pub found: LineEnding,
+    pub expected: LineEnding,
+}
+
+impl Mismatch {
+    /// Locate the line ended by the mismatched line ending in the text in which it was found.
+    pub fn location<'i>(
+        &self,
+        file_name: FileName,
+        src: &'i str,
+        tab_width: NonZeroUsize,
+    ) -> Location<'i> {
+        let line_start = src[..self.index].rfind(['\r', '\n']).map_or(0, |i| i + 1);
+        let start = Point::new(file_name, src)
+            .with_tab_width(tab_width)
+            .shift(&src[..line_start]);
+        let end = start.clone().shift(&src[line_start..self.index]);
+        Location::new(&start, &end)
+    }
+}
+
+/// Find the first line ending which differs from the one before it, leaving the text as it is.
+pub(crate) fn mismatch(text: &str) -> Option<Mismatch> {
+    let mut first = None;
+    let mut index = 0;
+    for (line, ending) in lines(text) {
+        index += line.len();
+        let ending = ending?;
+
+        match first {
+            None => first = Some(ending),
+            Some(expected) if expected != ending => {
+                return Some(Mismatch {
+                    index,
+                    found: ending,
+                    expected,
+                })
+            }
+            Some(_) => {}
+        }
+        index += ending.as_str().len();
+    }
+    None
+}
+
+/// Replace `\r\n` and `\r` line endings with `\n`.
+pub(crate) fn normalise(text: String) -> Normalised {
+    if !text.contains('\r') {
+        return Normalised {
+            text,
+            mismatch: None,
+        };
+    }
+
+    let mut normalised = String::with_capacity(text.len());
+    let mut first = None;
+    let mut mismatch = None;
+    for (line, ending) in lines(&text) {
+        normalised.push_str(line);
+        let Some(ending) = ending else {
+            break;
+        };
+
+        match first {
+            None => first = Some(ending),
+            Some(expected) if expected != ending && mismatch.is_none() => {
+                mismatch = Some(Mismatch {
+                    index: normalised.len(),
+                    found: ending,
+                    expected,
+                })
+            }
+            Some(_) => {}
+        }
+        normalised.push('\n');
+    }
+
+    Normalised {
+        text: normalised,
+        mismatch,
+    }
+}
+
+/// If the given text mixes line endings, rewrite it to use the most common style throughout.
+pub(crate) fn fix(text: &str) -> Option<String> {
+    let mut counts = [
+        (LineEnding::Lf, 0),
+        (LineEnding::CrLf, 0),
+        (LineEnding::Cr, 0),
+    ];
+    for (_, ending) in lines(text) {
+        if let Some(ending) = ending {
+            counts
+                .iter_mut()
+                .find(|(e, _)| *e == ending)
+                .expect("internal error: unknown line ending")
+                .1 += 1;
+        }
+    }
+    if counts.iter().filter(|(_, count)| *count > 0).count() < 2 {
+        return None;
+    }
+
+    let (predominant, _) = counts
+        .iter()
+        .max_by_key(|(_, count)| *count)
+        .expect("internal error: no line endings");
+    let mut fixed = String::with_capacity(text.len());
+    for (line, ending) in lines(text) {
+        fixed.push_str(line);
+        if ending.is_some() {
+            fixed.push_str(predominant.as_str());
+        }
+    }
+    Some(fixed)
+}
+
+/// Split text into lines, each paired with the line ending which follows it, if any.
+fn lines(text: &str) -> impl Iterator<Item = (&str, Option<LineEnding>)> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let curr = rest?;
+        let Some(idx) = curr.find(['\r', '\n']) else {
+            rest = None;
+            return Some((curr, None));
+        };
+
+        let ending = match &curr[idx..] {
+            s if s.starts_with("\r\n") => LineEnding::CrLf,
+            s if s.starts_with('\r') => LineEnding::Cr,
+            _ => LineEnding::Lf,
+        };
+        rest = Some(&curr[idx + ending.as_str().len()..]);
+        Some((&curr[..idx], Some(ending)))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::DEFAULT_TAB_WIDTH;
+
+    #[test]
+    fn normalise_consistent() {
+        for text in ["", "foo", "foo\nbar\n", "foo\r\nbar\r\n", "foo\rbar\r"] {
+            let normalised = normalise(text.into());
+            assert_eq!(
+                text.replace("\r\n", "\n").replace('\r', "\n"),
+                normalised.text
+            );
+            assert_eq!(None, normalised.mismatch, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn normalise_mixed() {
+        let normalised = normalise("foo\r\nbar\r\nbaz\nqux\r".into());
+        assert_eq!("foo\nbar\nbaz\nqux\n", normalised.text);
+        assert_eq!(
+            Some(Mismatch {
+                index: 11,
+                found: LineEnding::Lf,
+                expected: LineEnding::CrLf,
+            }),
+            normalised.mismatch
+        );
+
+        let loc = normalised.mismatch.unwrap().location(
+            FileName::new("main.em"),
+            &normalised.text,
+            DEFAULT_TAB_WIDTH,
+        );
+        assert_eq!("main.em:3:1-3", loc.to_string());
+    }
+
+    #[test]
+    fn mismatch_in_place() {
+        for text in ["", "foo", "foo\nbar\n", "foo\r\nbar\r\n", "foo\rbar\r"] {
+            assert_eq!(None, mismatch(text), "{text:?}");
+        }
+
+        let text = "foo\r\nbar\r\nbaz\nqux\r";
+        let found = mismatch(text);
+        assert_eq!(
+            Some(Mismatch {
+                index: 13,
+                found: LineEnding::Lf,
+                expected: LineEnding::CrLf,
+            }),
+            found
+        );
+
+        let loc = found
+            .unwrap()
+            .location(FileName::new("main.em"), text, DEFAULT_TAB_WIDTH);
+        assert_eq!("main.em:3:1-3", loc.to_string());
+    }
+
+    #[test]
+    fn fix_mixed() {
+        assert_eq!(None, fix("foo\nbar\n"));
+        assert_eq!(None, fix("foo\r\nbar\r\n"));
+        assert_eq!(
+            Some("foo\r\nbar\r\nbaz\r\nqux".into()),
+            fix("foo\r\nbar\nbaz\r\nqux")
+        );
+        assert_eq!(Some("foo\nbar\nbaz\n".into()), fix("foo\rbar\nbaz\n"));
+    }
+}
@@ -1,13 +1,9 @@
-use lazy_static::lazy_static;
-use regex::Regex;
 use std::fmt::{self, Display};
+use std::num::NonZeroUsize;
 
+use crate::context::DEFAULT_TAB_WIDTH;
 use crate::FileName;
 
-lazy_static! {
-    static ref NEWLINE: Regex = Regex::new("(\n|\r\n|\r)").unwrap();
-}
-
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Point<'input> {
     pub file_name: FileName,
@@ -15,6 +11,7 @@ pub struct Point<'input> {
     pub line: usize,
     pub col: usize,
     pub index: usize,
+    pub tab_width: NonZeroUsize,
 }
 
 impl<'input> Point<'input> {
@@ -25,21 +22,37 @@ impl<'input> Point<'input> {
             index: 0,
             line: 1,
             col: 1,
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
 
-    pub fn shift(mut self, text: &'input str) -> Self {
-        let lines: Vec<&str> = NEWLINE.split(text).collect();
-        let num_lines = lines.len();
-
-        self.line += num_lines - 1;
+    /// Set the number of columns between tab stops.
+    pub fn with_tab_width(mut self, tab_width: NonZeroUsize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
 
-        let last_line = lines[num_lines - 1];
-        let last_line_width: usize = last_line
-            .chars()
-            .map(|c| if c == '\t' { 4 } else { 1 })
-            .sum();
-        self.col = last_line_width + if num_lines > 1 { 1 } else { self.col };
+    pub fn shift(mut self, text: &'input str) -> Self {
+        let bytes = text.as_bytes();
+        let last_line = match memchr::memrchr2(b'\n', b'\r', bytes) {
+            Some(last_break) => {
+                self.line += line_breaks(bytes);
+                self.col = 1;
+                &text[last_break + 1..]
+            }
+            None => text,
+        };
+
+        // Each tab advances to the next tab stop, as in most editors.
+        let width = self.tab_width.get();
+        let mut segments = last_line.split('\t');
+        if let Some(first) = segments.next() {
+            self.col += first.chars().count();
+        }
+        for segment in segments {
+            self.col = (self.col.saturating_sub(1) / width + 1) * width + 1;
+            self.col += segment.chars().count();
+        }
 
         self.index += text.len();
 
@@ -47,6 +60,13 @@ impl<'input> Point<'input> {
     }
 }
 
+/// Count the line breaks in some text, where each of `\n`, `\r\n` and `\r` is one break.
+fn line_breaks(bytes: &[u8]) -> usize {
+    memchr::memchr2_iter(b'\n', b'\r', bytes)
+        .filter(|&i| !(bytes[i] == b'\r' && bytes.get(i + 1) == Some(&b'\n')))
+        .count()
+}
+
 impl Default for Point<'_> {
     fn default() -> Self {
         Self {
@@ -55,6 +75,7 @@ impl Default for Point<'_> {
             index: Default::default(),
             line: Default::default(),
             col: Default::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
 }
@@ -107,7 +128,25 @@ mod test {
         let end = start.shift(src);
 
         assert_eq!(13, end.index);
-        assert_eq!(20, end.col);
+        assert_eq!(18, end.col);
+
+        let start =
+            Point::new(FileName::new("fname"), src).with_tab_width(NonZeroUsize::new(8).unwrap());
+        let end = start.shift(src);
+
+        assert_eq!(13, end.index);
+        assert_eq!(22, end.col);
+    }
+
+    #[test]
+    fn tab_stops() {
+        let src = "ab\tc";
+        let start = Point::new(FileName::new("fname"), src);
+        assert_eq!(5, start.clone().shift("ab\t").col);
+        assert_eq!(6, start.clone().shift(src).col);
+        assert_eq!(5, start.clone().shift("ab").shift("\t").col);
+        assert_eq!(9, start.clone().shift("abcd\t").col);
+        assert_eq!(6, start.shift("a\tb\n\tc").col);
     }
 
     #[test]
@@ -123,4 +162,16 @@ mod test {
         assert_eq!(118, end.index);
         assert_eq!(8, end.col);
     }
+
+    #[test]
+    fn shift_mixed_line_breaks() {
+        let src = "foo\r\nbar\rbaz\n\r\nqux";
+        let end = Point::new(FileName::new("fname"), src).shift(src);
+        assert_eq!(5, end.line);
+        assert_eq!(4, end.col);
+
+        let end = Point::new(FileName::new("fname"), src).shift("foo\r");
+        assert_eq!(2, end.line);
+        assert_eq!(1, end.col);
+    }
 }
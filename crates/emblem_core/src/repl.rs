@@ -0,0 +1,290 @@
+use crate::context::Context;
+use crate::log::messages::{ExtensionFailure, ExtensionStage, Message};
+use crate::parser;
+use crate::{Action, EmblemResult, Input, Log};
+use derive_new::new;
+use mlua::{Error as MLuaError, Function, Lua, MultiValue, Result as MLuaResult, Value};
+use std::io::{self, BufRead, Write};
+
+const PROMPT: &str = "em> ";
+const MAX_DEPTH: usize = 3;
+const HELP: &str = "Enter a Lua expression or statement to evaluate it.
+
+Commands:
+  :help      show this message
+  :history   list the lines entered so far
+  :quit      leave the console";
+
+/// An interactive console for evaluating Lua in the environment seen by a document's extensions.
+#[derive(new)]
+pub struct Repl {
+    input: Input,
+}
+
+impl Action for Repl {
+    type Response = ();
+
+    fn run<'ctx>(&self, ctx: &'ctx mut Context<'ctx>) -> EmblemResult<'ctx, Self::Response> {
+        let diagnostics = ctx.diagnostic_params().clone();
+        EmblemResult::new(diagnostics.apply(self.console(ctx)), ())
+    }
+}
+
+impl Repl {
+    /// Load the document and run the console, returning any problems found.
+    fn console<'ctx>(&self, ctx: &'ctx mut Context<'ctx>) -> Vec<Log<'ctx>> {
+        if self.input == Input::Stdin {
+            return vec![
+                Log::error("cannot read a document from stdin in the console")
+                    .with_help("pass the path of the document to load instead"),
+            ];
+        }
+
+        let mut problems = match parser::parse_input(ctx, &self.input) {
+            Ok((_, problems)) => problems,
+            Err(e) => return vec![e.log()],
+        };
+
+        let ext_state = match ctx.extension_state() {
+            Ok(ext_state) => ext_state,
+            Err(e) => {
+                problems.push(ExtensionFailure::new(ExtensionStage::Setup, e.to_string()).log());
+                return problems;
+            }
+        };
+
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let greeting = match problems.len() {
+            0 => writeln!(out, "loaded {}", self.input),
+            n => writeln!(
+                out,
+                "loaded {} with {n} problem{}, reported on exit",
+                self.input,
+                if n == 1 { "" } else { "s" }
+            ),
+        };
+        let session =
+            greeting.and_then(|()| Session::new(ext_state.lua()).run(io::stdin().lock(), &mut out));
+        if let Err(e) = session {
+            problems.push(Log::error(format!("console failed: {e}")));
+        }
+
+        problems
+    }
+}
+
+/// The state of one interactive session.
+struct Session<'lua> {
+    lua: &'lua Lua,
+    history: Vec<String>,
+}
+
+impl<'lua> Session<'lua> {
+    fn new(lua: &'lua Lua) -> Self {
+        Self {
+            lua,
+            history: Vec::new(),
+        }
+    }
+
+    /// Read and evaluate lines until the input ends or the user quits.
+    fn run(&mut self, mut input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+        let mut line = String::new();
+        loop {
+            write!(out, "{PROMPT}")?;
+            out.flush()?;
+
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(out)?;
+                return Ok(());
+            }
+
+            match line.trim() {
+                "" => {}
+                ":q" | ":quit" => return Ok(()),
+                ":help" => writeln!(out, "{HELP}")?,
+                ":history" => {
+                    for (i, entry) in self.history.iter().enumerate() {
+                        writeln!(out, "{:>4}  {entry}", i + 1)?;
+                    }
+                }
+                cmd if cmd.starts_with(':') => writeln!(out, "unknown command {cmd}, try :help")?,
+                code => {
+                    self.history.push(code.into());
+                    match self.eval(code) {
+                        Ok(Some(result)) => writeln!(out, "{result}")?,
+                        Ok(None) => {}
+                        Err(e) => writeln!(out, "error: {e}")?,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evaluate a line of Lua, returning a rendering of its results, if there are any. The line is
+    /// first treated as an expression, then as a statement if it is not one.
+    fn eval(&self, code: &str) -> MLuaResult<Option<String>> {
+        let expr = format!("return {code}");
+        let values: MultiValue = match self.lua.load(expr.as_str()).set_name("=repl")?.eval() {
+            Err(MLuaError::SyntaxError { .. }) => self.lua.load(code).set_name("=repl")?.eval()?,
+            values => values?,
+        };
+        if values.is_empty() {
+            return Ok(None);
+        }
+
+        let tostring: Function = self.lua.globals().get("tostring")?;
+        let rendered: Vec<_> = values
+            .iter()
+            .map(|value| pretty(&tostring, value, 0))
+            .collect();
+        Ok(Some(rendered.join("\t")))
+    }
+}
+
+/// Render a Lua value for display. Tables are expanded up to a fixed depth, which also stops
+/// cyclic tables from being expanded forever.
+fn pretty(tostring: &Function<'_>, value: &Value<'_>, depth: usize) -> String {
+    match value {
+        Value::Nil => "nil".into(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("{:?}", s.to_string_lossy()),
+        Value::Function(_) => "<function>".into(),
+        Value::Table(_) if depth >= MAX_DEPTH => "{...}".into(),
+        Value::Table(table) => {
+            let mut entries: Vec<(Value<'_>, Value<'_>)> =
+                table.clone().pairs().filter_map(|pair| pair.ok()).collect();
+            if entries.is_empty() {
+                return "{}".into();
+            }
+            entries.sort_by_cached_key(|(key, _)| match key {
+                Value::Integer(i) => (0, *i, String::new()),
+                key => (1, 0, pretty(tostring, key, MAX_DEPTH)),
+            });
+
+            let is_sequence = entries
+                .iter()
+                .enumerate()
+                .all(|(i, (key, _))| matches!(key, Value::Integer(k) if *k == i as i64 + 1));
+            let fields: Vec<_> = entries
+                .iter()
+                .map(|(key, value)| {
+                    let value = pretty(tostring, value, depth + 1);
+                    match key {
+                        _ if is_sequence => value,
+                        Value::String(s) if is_identifier(s.as_bytes()) => {
+                            format!("{} = {value}", s.to_string_lossy())
+                        }
+                        key => format!("[{}] = {value}", pretty(tostring, key, MAX_DEPTH)),
+                    }
+                })
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        value => tostring
+            .call::<_, String>(value.clone())
+            .unwrap_or_else(|_| format!("<{}>", value.type_name())),
+    }
+}
+
+fn is_identifier(s: &[u8]) -> bool {
+    match s {
+        [first, rest @ ..] => {
+            (first.is_ascii_alphabetic() || *first == b'_')
+                && rest.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'_')
+        }
+        [] => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session(lines: &[&str]) -> String {
+        let ctx = Context::test_new();
+        let ext_state = ctx.extension_state().unwrap();
+        let mut out = Vec::new();
+        Session::new(ext_state.lua())
+            .run(lines.join("\n").as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn eval(code: &str) -> String {
+        let ctx = Context::test_new();
+        let ext_state = ctx.extension_state().unwrap();
+        let result = Session::new(ext_state.lua()).eval(code);
+        match result {
+            Ok(Some(result)) => result,
+            Ok(None) => "(nothing)".into(),
+            Err(e) => panic!("failed to evaluate {code:?}: {e}"),
+        }
+    }
+
+    #[test]
+    fn expressions_and_statements() {
+        assert_eq!("3", eval("1 + 2"));
+        assert_eq!("\"foo\"\ttrue\tnil", eval("'foo', true, nil"));
+        assert_eq!("(nothing)", eval("x = 1"));
+        assert_eq!("<function>", eval("tostring"));
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!("{}", eval("{}"));
+        assert_eq!("{ 1, 2, \"three\" }", eval("{ 1, 2, 'three' }"));
+        assert_eq!(
+            "{ [1] = true, [\"a b\"] = 2, x = { y = {} } }",
+            eval("{ x = { y = {} }, [1] = true, ['a b'] = 2, [3] = nil }")
+        );
+        assert_eq!(
+            "{ self = { self = { self = {...} } } }",
+            eval("(function() local t = {}; t.self = t; return t end)()")
+        );
+    }
+
+    #[test]
+    fn globals_persist() {
+        let out = session(&["x = 40", "x + 2"]);
+        assert!(out.contains("42"), "{out}");
+    }
+
+    #[test]
+    fn errors() {
+        let out = session(&["error('oh no')", "1 +"]);
+        assert_eq!(2, out.matches("em> error: ").count(), "{out}");
+        assert!(out.contains("oh no"), "{out}");
+    }
+
+    #[test]
+    fn commands() {
+        let out = session(&["1", "", "2", ":history", ":quit", "3"]);
+        assert!(out.contains("   1  1\n   2  2\n"), "{out}");
+        assert!(!out.contains("\n3\n"), "{out}");
+
+        let out = session(&[":help"]);
+        assert!(out.contains(":history"), "{out}");
+
+        let out = session(&[":frobnicate"]);
+        assert!(out.contains("unknown command :frobnicate"), "{out}");
+    }
+
+    #[test]
+    fn sandboxed() {
+        assert_eq!("nil", eval("io.open('main.em')"));
+    }
+
+    #[test]
+    fn identifiers() {
+        assert!(is_identifier(b"foo_1"));
+        assert!(is_identifier(b"_"));
+        assert!(!is_identifier(b""));
+        assert!(!is_identifier(b"1foo"));
+        assert!(!is_identifier(b"a b"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    build::journal::{self, JournalEntry},
+    context::Context,
+    Action, EmblemResult, Log,
+};
+use derive_new::new;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The width of the longest bar in a history graph.
+const GRAPH_WIDTH: usize = 40;
+
+/// Summarises the builds recorded in a build journal.
+#[derive(new)]
+pub struct StatsReporter {
+    journal: PathBuf,
+    history: bool,
+}
+
+impl Action for StatsReporter {
+    type Response = Option<String>;
+
+    fn run<'ctx>(&self, _: &'ctx mut Context) -> EmblemResult<'ctx, Self::Response> {
+        let entries = match journal::read(&self.journal) {
+            Ok(entries) => entries,
+            Err(e) => {
+                let problem = Log::error(format!(
+                    "failed to read build journal {}: {e}",
+                    self.journal.display()
+                ))
+                .with_help("record builds by running em build --journal");
+                return EmblemResult::new(vec![problem], None);
+            }
+        };
+
+        if entries.is_empty() {
+            return EmblemResult::new(vec![Log::warn("no builds have been recorded")], None);
+        }
+
+        let report = if self.history {
+            history(&entries)
+        } else {
+            summary(&entries)
+        };
+        EmblemResult::new(vec![], Some(report))
+    }
+
+    fn output<'ctx>(&self, resp: Self::Response) -> EmblemResult<'ctx, ()> {
+        if let Some(report) = resp {
+            print!("{report}");
+        }
+        EmblemResult::new(vec![], ())
+    }
+}
+
+fn summary(entries: &[JournalEntry]) -> String {
+    let last = entries.last().expect("internal error: no journal entries");
+    let total: Duration = entries.iter().map(|e| e.duration).sum();
+    let mean = total / entries.len() as u32;
+
+    let mut ret = String::new();
+    writeln!(ret, "builds recorded: {}", entries.len()).unwrap();
+    writeln!(ret, "last build: {}", describe(last)).unwrap();
+    writeln!(ret, "mean build time: {:.2}s", mean.as_secs_f64()).unwrap();
+    ret
+}
+
+/// Graph the duration of each recorded build, oldest first.
+fn history(entries: &[JournalEntry]) -> String {
+    let longest = entries.iter().map(|e| e.duration).max().unwrap_or_default();
+    let index_width = entries.len().to_string().len();
+
+    let mut ret = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let bar_len = if longest.is_zero() {
+            0
+        } else {
+            (GRAPH_WIDTH as f64 * entry.duration.as_secs_f64() / longest.as_secs_f64()).round()
+                as usize
+        };
+        writeln!(
+            ret,
+            "{:>index_width$} {:<GRAPH_WIDTH$} {}",
+            i + 1,
+            "#".repeat(bar_len.max(1)),
+            describe(entry),
+        )
+        .unwrap();
+    }
+    ret
+}
+
+fn describe(entry: &JournalEntry) -> String {
+    let mut ret = format!(
+        "{:.2}s, {} warning{}, {} error{}",
+        entry.duration.as_secs_f64(),
+        entry.warnings,
+        if entry.warnings == 1 { "" } else { "s" },
+        entry.errors,
+        if entry.errors == 1 { "" } else { "s" },
+    );
+    if let Some(size) = entry.output_size {
+        write!(ret, ", {size} bytes").unwrap();
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(millis: u64, warnings: usize, output_size: Option<u64>) -> JournalEntry {
+        JournalEntry {
+            timestamp: 0,
+            duration: Duration::from_millis(millis),
+            warnings,
+            errors: 0,
+            output_size,
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let entries = [entry(1000, 0, Some(10)), entry(2000, 1, Some(20))];
+        assert_eq!(
+            indoc::indoc!(
+                "
+                builds recorded: 2
+                last build: 2.00s, 1 warning, 0 errors, 20 bytes
+                mean build time: 1.50s
+                "
+            ),
+            super::summary(&entries)
+        );
+    }
+
+    #[test]
+    fn history() {
+        let entries = [
+            entry(500, 0, Some(10)),
+            entry(2000, 2, None),
+            entry(1000, 0, Some(30)),
+        ];
+        let history = super::history(&entries);
+        let lines: Vec<_> = history.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            format!(
+                "1 {:<40} 0.50s, 0 warnings, 0 errors, 10 bytes",
+                "#".repeat(10)
+            ),
+            lines[0]
+        );
+        assert_eq!(
+            format!("2 {} 2.00s, 2 warnings, 0 errors", "#".repeat(40)),
+            lines[1]
+        );
+        assert_eq!(
+            format!(
+                "3 {:<40} 1.00s, 0 warnings, 0 errors, 30 bytes",
+                "#".repeat(20)
+            ),
+            lines[2]
+        );
+    }
+
+    #[test]
+    fn missing_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = Context::test_new();
+        let reporter = StatsReporter::new(dir.path().join("journal"), false);
+        let result = reporter.run(&mut ctx);
+        assert!(result.response.is_none());
+        assert_eq!(1, result.logs.len());
+        assert!(result.logs[0]
+            .msg()
+            .starts_with("failed to read build journal"));
+    }
+}
@@ -0,0 +1,295 @@
+use std::{error::Error, fmt::Display};
+
+/// An sRGB colour.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Colour {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Colour {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    fn named(name: &str) -> Option<Self> {
+        Some(match name {
+            "black" => Self::rgb(0, 0, 0),
+            "white" => Self::rgb(255, 255, 255),
+            "red" => Self::rgb(255, 0, 0),
+            "green" => Self::rgb(0, 128, 0),
+            "blue" => Self::rgb(0, 0, 255),
+            "yellow" => Self::rgb(255, 255, 0),
+            "cyan" => Self::rgb(0, 255, 255),
+            "magenta" => Self::rgb(255, 0, 255),
+            "orange" => Self::rgb(255, 165, 0),
+            "purple" => Self::rgb(128, 0, 128),
+            "grey" | "gray" => Self::rgb(128, 128, 128),
+            _ => return None,
+        })
+    }
+
+    fn hex(digits: &str) -> Option<Self> {
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |i: usize, len: usize| {
+            let c = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).ok()?;
+            Some(if len == 1 { c * 0x11 } else { c })
+        };
+        let len = match digits.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+        Some(Self::rgb(
+            channel(0, len)?,
+            channel(1, len)?,
+            channel(2, len)?,
+        ))
+    }
+
+    fn functional(args: &str) -> Option<Self> {
+        let channels = args
+            .split(',')
+            .map(|c| c.trim().parse().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        match channels[..] {
+            [r, g, b] => Some(Self::rgb(r, g, b)),
+            _ => None,
+        }
+    }
+
+    /// The relative luminance of this colour, as defined by WCAG 2.
+    pub fn relative_luminance(&self) -> f64 {
+        let linear = |c: u8| {
+            let c = f64::from(c) / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// The contrast ratio between this colour and another, from 1 (none) to 21 (black on white).
+    pub fn contrast_ratio(&self, other: &Self) -> f64 {
+        let (l1, l2) = (self.relative_luminance(), other.relative_luminance());
+        let (lighter, darker) = if l1 > l2 { (l1, l2) } else { (l2, l1) };
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    /// Darken this colour by the given fraction, between 0 and 1.
+    pub fn darken(&self, amount: f64) -> Self {
+        let scale = 1.0 - amount.clamp(0.0, 1.0);
+        let channel = |c: u8| (f64::from(c) * scale).round() as u8;
+        Self::rgb(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    /// Lighten this colour by the given fraction, between 0 and 1.
+    pub fn lighten(&self, amount: f64) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        let channel = |c: u8| (f64::from(c) + f64::from(255 - c) * amount).round() as u8;
+        Self::rgb(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    /// Adjust this colour until its contrast against the given background is at least
+    /// `min_ratio`. Colours are lightened against dark backgrounds and darkened against light
+    /// ones. If no amount of adjustment is enough, black or white is returned, whichever contrasts
+    /// more with the background.
+    pub fn with_min_contrast(&self, background: &Self, min_ratio: f64) -> Self {
+        let black = Self::rgb(0, 0, 0);
+        let white = Self::rgb(255, 255, 255);
+        let lighten = white.contrast_ratio(background) > black.contrast_ratio(background);
+
+        for step in 0..=20 {
+            let amount = f64::from(step) / 20.0;
+            let ret = if lighten {
+                self.lighten(amount)
+            } else {
+                self.darken(amount)
+            };
+            if ret.contrast_ratio(background) >= min_ratio {
+                return ret;
+            }
+        }
+
+        if lighten {
+            white
+        } else {
+            black
+        }
+    }
+}
+
+impl TryFrom<&str> for Colour {
+    type Error = ColourError;
+
+    fn try_from(raw: &str) -> Result<Self, Self::Error> {
+        let trimmed = raw.trim();
+        let colour = if let Some(digits) = trimmed.strip_prefix('#') {
+            Self::hex(digits)
+        } else if let Some(args) = trimmed
+            .strip_prefix("rgb(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            Self::functional(args)
+        } else {
+            Self::named(&trimmed.to_lowercase())
+        };
+        colour.ok_or_else(|| ColourError::new(raw))
+    }
+}
+
+impl Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ColourError {
+    raw: String,
+}
+
+impl ColourError {
+    fn new(raw: &str) -> Self {
+        Self { raw: raw.into() }
+    }
+}
+
+impl Display for ColourError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unrecognised colour: expected a name, #rgb, #rrggbb or rgb(r, g, b), got {:?}",
+            self.raw
+        )
+    }
+}
+
+impl Error for ColourError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let tests = [
+            ("black", Colour::rgb(0, 0, 0)),
+            ("White", Colour::rgb(255, 255, 255)),
+            ("gray", Colour::rgb(128, 128, 128)),
+            ("#fff", Colour::rgb(255, 255, 255)),
+            ("#1a2B3c", Colour::rgb(0x1a, 0x2b, 0x3c)),
+            ("rgb(1, 2, 3)", Colour::rgb(1, 2, 3)),
+            (" rgb(255,0,10) ", Colour::rgb(255, 0, 10)),
+        ];
+        for (raw, expected) in tests {
+            assert_eq!(Ok(expected), Colour::try_from(raw), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for raw in [
+            "",
+            "blurple",
+            "#",
+            "#ff",
+            "#fffff",
+            "#ggg",
+            "#ffé",
+            "rgb(1, 2)",
+            "rgb(1, 2, 256)",
+            "rgb(1, 2, 3",
+        ] {
+            let err = Colour::try_from(raw).unwrap_err();
+            assert!(err.to_string().starts_with("unrecognised colour"));
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("#000000", Colour::rgb(0, 0, 0).to_string());
+        assert_eq!("#0a10ff", Colour::rgb(10, 16, 255).to_string());
+        assert_eq!(Ok(Colour::rgb(10, 16, 255)), Colour::try_from("#0a10ff"));
+    }
+
+    #[test]
+    fn contrast_ratio() {
+        let black = Colour::rgb(0, 0, 0);
+        let white = Colour::rgb(255, 255, 255);
+        assert!((black.contrast_ratio(&white) - 21.0).abs() < 1e-9);
+        assert!((white.contrast_ratio(&black) - 21.0).abs() < 1e-9);
+        assert!((white.contrast_ratio(&white) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn darken() {
+        let colour = Colour::rgb(200, 100, 50);
+        assert_eq!(colour, colour.darken(0.0));
+        assert_eq!(Colour::rgb(100, 50, 25), colour.darken(0.5));
+        assert_eq!(Colour::rgb(0, 0, 0), colour.darken(1.0));
+        assert_eq!(Colour::rgb(0, 0, 0), colour.darken(2.0));
+    }
+
+    #[test]
+    fn with_min_contrast() {
+        let white = Colour::rgb(255, 255, 255);
+        let black = Colour::rgb(0, 0, 0);
+        assert_eq!(black, black.with_min_contrast(&white, 4.5));
+
+        let yellow = Colour::rgb(255, 255, 0);
+        let adjusted = yellow.with_min_contrast(&white, 4.5);
+        assert!(adjusted.contrast_ratio(&white) >= 4.5);
+
+        assert_eq!(black, white.with_min_contrast(&white, 100.0));
+    }
+
+    #[test]
+    fn lighten() {
+        let colour = Colour::rgb(55, 155, 205);
+        assert_eq!(colour, colour.lighten(0.0));
+        assert_eq!(Colour::rgb(155, 205, 230), colour.lighten(0.5));
+        assert_eq!(Colour::rgb(255, 255, 255), colour.lighten(1.0));
+        assert_eq!(Colour::rgb(255, 255, 255), colour.lighten(2.0));
+    }
+
+    #[test]
+    fn with_min_contrast_dark_background() {
+        let white = Colour::rgb(255, 255, 255);
+        let black = Colour::rgb(0, 0, 0);
+        assert_eq!(white, white.with_min_contrast(&black, 4.5));
+
+        let navy = Colour::rgb(0, 0, 128);
+        let adjusted = navy.with_min_contrast(&black, 4.5);
+        assert!(adjusted.contrast_ratio(&black) >= 4.5);
+        assert!(adjusted.relative_luminance() > navy.relative_luminance());
+
+        assert_eq!(white, black.with_min_contrast(&black, 100.0));
+    }
+
+    #[test]
+    fn with_min_contrast_unreachable() {
+        let grey = Colour::rgb(128, 128, 128);
+        let black = Colour::rgb(0, 0, 0);
+        let red = Colour::rgb(255, 0, 0);
+        assert_eq!(black, red.with_min_contrast(&grey, 10.0));
+    }
+}
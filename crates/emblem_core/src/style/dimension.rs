@@ -0,0 +1,268 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    ops::{Mul, Neg},
+};
+
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// A length with a unit, for example `12pt` or `1.5em`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dimension {
+    value: f64,
+    unit: Unit,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// Points, 1/72 of an inch
+    Pt,
+
+    /// Millimetres
+    Mm,
+
+    /// Centimetres
+    Cm,
+
+    /// Multiples of the current font size
+    Em,
+
+    /// Percentage of the containing length
+    Percent,
+
+    /// Fraction of the remaining free space
+    Fr,
+}
+
+impl Unit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pt => "pt",
+            Self::Mm => "mm",
+            Self::Cm => "cm",
+            Self::Em => "em",
+            Self::Percent => "%",
+            Self::Fr => "fr",
+        }
+    }
+
+    /// The number of points in one of this unit, if this is an absolute unit.
+    fn pts(&self) -> Option<f64> {
+        match self {
+            Self::Pt => Some(1.0),
+            Self::Mm => Some(PT_PER_MM),
+            Self::Cm => Some(10.0 * PT_PER_MM),
+            Self::Em | Self::Percent | Self::Fr => None,
+        }
+    }
+}
+
+/// Lengths against which relative dimensions are resolved, in points.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DimensionContext {
+    pub font_size: f64,
+    pub container_size: f64,
+}
+
+impl Dimension {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.unit.pts().is_some()
+    }
+
+    /// Add two dimensions. Absolute dimensions are added in points if their units differ;
+    /// relative dimensions may only be added to others of the same unit.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.unit == other.unit {
+            return Some(Self::new(self.value + other.value, self.unit));
+        }
+
+        let lhs = self.unit.pts()? * self.value;
+        let rhs = other.unit.pts()? * other.value;
+        Some(Self::new(lhs + rhs, Unit::Pt))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&-*other)
+    }
+
+    /// Resolve this dimension to a length in points. Fractional dimensions depend on the space
+    /// left over after layout, so cannot be resolved here.
+    pub fn resolve(&self, ctx: &DimensionContext) -> Option<f64> {
+        match self.unit {
+            Unit::Em => Some(self.value * ctx.font_size),
+            Unit::Percent => Some(self.value / 100.0 * ctx.container_size),
+            Unit::Fr => None,
+            Unit::Pt | Unit::Mm | Unit::Cm => self.unit.pts().map(|pts| self.value * pts),
+        }
+    }
+}
+
+impl TryFrom<&str> for Dimension {
+    type Error = DimensionError;
+
+    fn try_from(raw: &str) -> Result<Self, Self::Error> {
+        let trimmed = raw.trim();
+        let unit_start = trimmed
+            .find(|c: char| c.is_alphabetic() || c == '%')
+            .ok_or_else(|| DimensionError::new(raw))?;
+        let (value, unit) = trimmed.split_at(unit_start);
+
+        let unit = match unit {
+            "pt" => Unit::Pt,
+            "mm" => Unit::Mm,
+            "cm" => Unit::Cm,
+            "em" => Unit::Em,
+            "%" => Unit::Percent,
+            "fr" => Unit::Fr,
+            _ => return Err(DimensionError::new(raw)),
+        };
+        let value: f64 = value
+            .trim_end()
+            .parse()
+            .map_err(|_| DimensionError::new(raw))?;
+        if !value.is_finite() {
+            return Err(DimensionError::new(raw));
+        }
+
+        Ok(Self::new(value, unit))
+    }
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.as_str())
+    }
+}
+
+impl Mul<f64> for Dimension {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.value * rhs, self.unit)
+    }
+}
+
+impl Neg for Dimension {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.value, self.unit)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DimensionError {
+    raw: String,
+}
+
+impl DimensionError {
+    fn new(raw: &str) -> Self {
+        Self { raw: raw.into() }
+    }
+}
+
+impl Display for DimensionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unrecognised dimension: expected a number followed by pt, mm, cm, em, % or fr, got {:?}",
+            self.raw
+        )
+    }
+}
+
+impl Error for DimensionError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let tests = [
+            ("12pt", Dimension::new(12.0, Unit::Pt)),
+            ("1.5em", Dimension::new(1.5, Unit::Em)),
+            (" 20mm ", Dimension::new(20.0, Unit::Mm)),
+            ("2 cm", Dimension::new(2.0, Unit::Cm)),
+            ("50%", Dimension::new(50.0, Unit::Percent)),
+            ("1fr", Dimension::new(1.0, Unit::Fr)),
+            ("-3pt", Dimension::new(-3.0, Unit::Pt)),
+            (".5em", Dimension::new(0.5, Unit::Em)),
+        ];
+        for (raw, expected) in tests {
+            assert_eq!(Ok(expected), Dimension::try_from(raw), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for raw in [
+            "", "12", "pt", "12px", "12 p t", "1.2.3em", "infpt", "NaNem", "12%%",
+        ] {
+            let err = Dimension::try_from(raw).unwrap_err();
+            assert!(err.to_string().starts_with("unrecognised dimension"));
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("12pt", Dimension::new(12.0, Unit::Pt).to_string());
+        assert_eq!("1.5em", Dimension::new(1.5, Unit::Em).to_string());
+        assert_eq!("50%", Dimension::new(50.0, Unit::Percent).to_string());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let pt = Dimension::new(2.0, Unit::Pt);
+        assert_eq!(Some(Dimension::new(4.0, Unit::Pt)), pt.checked_add(&pt));
+        assert_eq!(Some(Dimension::new(0.0, Unit::Pt)), pt.checked_sub(&pt));
+        assert_eq!(Dimension::new(6.0, Unit::Pt), pt * 3.0);
+        assert_eq!(Dimension::new(-2.0, Unit::Pt), -pt);
+
+        let mixed = Dimension::new(1.0, Unit::Cm)
+            .checked_add(&Dimension::new(10.0, Unit::Mm))
+            .unwrap();
+        assert_eq!(Unit::Pt, mixed.unit());
+        assert_close(20.0 * PT_PER_MM, mixed.value());
+
+        let em = Dimension::new(1.0, Unit::Em);
+        assert_eq!(Some(Dimension::new(2.0, Unit::Em)), em.checked_add(&em));
+        assert_eq!(None, em.checked_add(&pt));
+        assert_eq!(None, pt.checked_add(&Dimension::new(1.0, Unit::Fr)));
+    }
+
+    #[test]
+    fn resolve() {
+        let ctx = DimensionContext {
+            font_size: 10.0,
+            container_size: 400.0,
+        };
+        let resolve = |raw| Dimension::try_from(raw).unwrap().resolve(&ctx);
+
+        assert_eq!(Some(12.0), resolve("12pt"));
+        assert_close(72.0, resolve("25.4mm").unwrap());
+        assert_close(72.0, resolve("2.54cm").unwrap());
+        assert_eq!(Some(15.0), resolve("1.5em"));
+        assert_eq!(Some(100.0), resolve("25%"));
+        assert_eq!(None, resolve("1fr"));
+    }
+}
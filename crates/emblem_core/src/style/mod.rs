@@ -0,0 +1,68 @@
+mod colour;
+mod dimension;
+
+pub use colour::{Colour, ColourError};
+pub use dimension::{Dimension, DimensionContext, DimensionError, Unit};
+
+/// Text-styling properties which may be applied to a span of text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub colour: Option<Colour>,
+    pub background: Option<Colour>,
+    pub underline: bool,
+}
+
+impl TextStyle {
+    /// The minimum contrast ratio between text and its background for body text to be legible,
+    /// following WCAG 2 level AA.
+    pub const MIN_CONTRAST: f64 = 4.5;
+
+    /// Returns the text colour to use for this style such that it is legible against its
+    /// background, lightening or darkening the requested colour if necessary.
+    pub fn legible_colour(&self) -> Option<Colour> {
+        let colour = self.colour?;
+        Some(match self.background {
+            Some(background) => colour.with_min_contrast(&background, Self::MIN_CONTRAST),
+            None => colour,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn legible_colour() {
+        assert_eq!(None, TextStyle::default().legible_colour());
+
+        let red = Colour::rgb(255, 0, 0);
+        let style = TextStyle {
+            colour: Some(red),
+            ..Default::default()
+        };
+        assert_eq!(Some(red), style.legible_colour());
+
+        let yellow = Colour::rgb(255, 255, 0);
+        let white = Colour::rgb(255, 255, 255);
+        let style = TextStyle {
+            colour: Some(yellow),
+            background: Some(white),
+            underline: false,
+        };
+        let legible = style.legible_colour().unwrap();
+        assert_ne!(yellow, legible);
+        assert!(legible.contrast_ratio(&white) >= TextStyle::MIN_CONTRAST);
+
+        let navy = Colour::rgb(0, 0, 128);
+        let black = Colour::rgb(0, 0, 0);
+        let style = TextStyle {
+            colour: Some(navy),
+            background: Some(black),
+            underline: false,
+        };
+        let legible = style.legible_colour().unwrap();
+        assert_ne!(navy, legible);
+        assert!(legible.contrast_ratio(&black) >= TextStyle::MIN_CONTRAST);
+    }
+}
@@ -1,12 +1,124 @@
-#[derive(Debug)]
-pub enum Version {
-    V1_0,
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A version of the Emblem language, written `vMAJOR.MINOR`. Documents declare the version they
+/// target so that they remain buildable as the language changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Version {
+    major: u32,
+    minor: u32,
 }
 
 impl Version {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Self::V1_0 => "v1.0",
+    pub const V1_0: Self = Self::new(1, 0);
+
+    /// The newest version understood by this build.
+    pub const CURRENT: Self = Self::V1_0;
+
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Returns whether a document which targets this version can be built by a toolchain which
+    /// understands `toolchain`. Minor versions only add to the language, so newer minor versions
+    /// can build older documents, but a change of major version may break them.
+    pub fn is_compatible_with(&self, toolchain: Self) -> bool {
+        self.major == toolchain.major && self.minor <= toolchain.minor
+    }
+
+    /// Returns whether this build can build documents which target this version.
+    pub fn is_supported(&self) -> bool {
+        self.is_compatible_with(Self::CURRENT)
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Self::CURRENT
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    /// Parse a version of the form `v1.0`. The leading `v` and the minor version may be omitted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid version '{s}': expected a version such as 'v1.0'");
+
+        let raw = s.strip_prefix('v').unwrap_or(s);
+        let (major, minor) = raw.split_once('.').unwrap_or((raw, "0"));
+        let parse = |part: &str| {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse().map_err(|_| invalid())
+        };
+        Ok(Self::new(parse(major)?, parse(minor)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Version::V1_0), "v1.0".parse());
+        assert_eq!(Ok(Version::V1_0), "1.0".parse());
+        assert_eq!(Ok(Version::V1_0), "v1".parse());
+        assert_eq!(Ok(Version::new(2, 13)), "v2.13".parse());
+
+        for invalid in [
+            "", "v", "v1.", "v.1", "v1.0.0", "v1.x", "v-1.0", "v+1.0", "1.0 ",
+        ] {
+            assert_eq!(
+                Err(format!(
+                    "invalid version '{invalid}': expected a version such as 'v1.0'"
+                )),
+                invalid.parse::<Version>(),
+            );
         }
     }
+
+    #[test]
+    fn display() {
+        assert_eq!("v1.0", Version::V1_0.to_string());
+        assert_eq!("v2.13", Version::new(2, 13).to_string());
+
+        let version = Version::new(3, 1);
+        assert_eq!(Ok(version), version.to_string().parse());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Version::new(1, 0) < Version::new(1, 1));
+        assert!(Version::new(1, 10) < Version::new(2, 0));
+        assert_eq!(Version::CURRENT, Version::default());
+    }
+
+    #[test]
+    fn compatibility() {
+        assert!(Version::new(1, 0).is_compatible_with(Version::new(1, 0)));
+        assert!(Version::new(1, 0).is_compatible_with(Version::new(1, 2)));
+        assert!(!Version::new(1, 2).is_compatible_with(Version::new(1, 0)));
+        assert!(!Version::new(1, 0).is_compatible_with(Version::new(2, 0)));
+        assert!(!Version::new(2, 0).is_compatible_with(Version::new(1, 9)));
+
+        assert!(Version::V1_0.is_supported());
+        assert!(!Version::new(Version::CURRENT.major() + 1, 0).is_supported());
+    }
 }
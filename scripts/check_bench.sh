@@ -0,0 +1,21 @@
+#!/bin/bash
+
+# Fail if any benchmark has slowed by more than the given percentage relative to the baseline it
+# was last compared against, for example after `cargo bench -p benchmarks -- --baseline base`.
+
+threshold=${1:-10}
+failed=0
+
+for change in target/criterion/*/*/change/estimates.json; do
+	[[ -f $change ]] || continue
+
+	bench=$(dirname "$(dirname "$change")")
+	bench=${bench#target/criterion/}
+	percent=$(jq '.mean.point_estimate * 100' "$change")
+	if (( $(echo "$percent > $threshold" | bc -l) )); then
+		printf '%s regressed by %.1f%% (threshold %s%%)\n' "$bench" "$percent" "$threshold"
+		failed=1
+	fi
+done
+
+exit $failed